@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 
-use crate::{camera, loot, player, projectile, world};
+use crate::{camera, data, inventory, loot, player, projectile, world};
 
 const VIEWPORT_HEIGHT: f32 = 20.0;
 const CAMERA_OFFSET: Vec3 = Vec3::new(
@@ -10,14 +12,14 @@ const CAMERA_OFFSET: Vec3 = Vec3::new(
     VIEWPORT_HEIGHT * 0.5,
 );
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct MeshMaterial {
     pub mesh: Handle<Mesh>,
     pub material: Handle<StandardMaterial>,
 }
 
 impl MeshMaterial {
-    fn gen_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
+    pub fn gen_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
         (
             Mesh3d(self.mesh.clone()),
             MeshMaterial3d(self.material.clone()),
@@ -25,16 +27,32 @@ impl MeshMaterial {
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum LootModelAsset {
+    Scene(Handle<Scene>),
+    Mesh(MeshMaterial),
+}
+
+#[derive(Debug, Clone)]
+pub struct LootAsset {
+    pub model: LootModelAsset,
+    pub rarity_ring: MeshMaterial,
+}
+
 #[derive(Debug, Default, Resource)]
 pub struct GameAssets {
     pub player_model: Handle<Scene>,
     pub _player_animation_graph: Handle<AnimationGraph>,
 
-    weapon_mesh: MeshMaterial,
-    ammo_mesh: MeshMaterial,
-    throwable_mesh: MeshMaterial,
-    consumable_mesh: MeshMaterial,
+    weapon_loot: HashMap<data::WeaponType, LootAsset>,
+    ammo_loot: HashMap<data::AmmoType, LootAsset>,
+    throwable_loot: HashMap<data::ThrowableType, LootAsset>,
+    consumable_loot: HashMap<data::ConsumableType, LootAsset>,
+    armor_loot: HashMap<data::ArmorType, LootAsset>,
+    backpack_loot: HashMap<data::BackpackType, LootAsset>,
     bullet_mesh: MeshMaterial,
+    rocket_mesh: MeshMaterial,
+    grenade_mesh: MeshMaterial,
 
     floor_mesh: MeshMaterial,
     wall_mesh: MeshMaterial,
@@ -43,6 +61,7 @@ pub struct GameAssets {
 }
 
 impl GameAssets {
+    #[allow(clippy::too_many_arguments)]
     fn load(
         &mut self,
         commands: &mut Commands,
@@ -50,6 +69,8 @@ impl GameAssets {
         meshes: &mut Assets<Mesh>,
         materials: &mut Assets<StandardMaterial>,
         animation_graphs: &mut Assets<AnimationGraph>,
+        projectile_datum: &data::ProjectileDatum,
+        loot_data: &loot::LootData,
     ) {
         // world
         self.floor_mesh = world::load_floor_assets(meshes, materials);
@@ -61,13 +82,88 @@ impl GameAssets {
         self.player_model = player::load_player_assets(commands, asset_server, animation_graphs);
 
         // loot
-        self.weapon_mesh = loot::load_weapon_assets(meshes, materials);
-        self.ammo_mesh = loot::load_ammo_assets(meshes, materials);
-        self.throwable_mesh = loot::load_throwable_assets(meshes, materials);
-        self.consumable_mesh = loot::load_consumable_assets(meshes, materials);
+        let rarity_rings = loot::load_rarity_ring_assets(meshes, materials);
+        let loot_asset = |model: LootModelAsset, rarity: data::Rarity| LootAsset {
+            model,
+            rarity_ring: rarity_rings.get(&rarity).unwrap().clone(),
+        };
+
+        self.weapon_loot = loot_data
+            .weapon
+            .iter()
+            .map(|(weapon_type, data)| {
+                let model =
+                    loot::load_loot_model_assets(asset_server, meshes, materials, &data.model);
+                (*weapon_type, loot_asset(model, data.rarity))
+            })
+            .collect();
+        self.ammo_loot = loot_data
+            .ammo
+            .iter()
+            .map(|(ammo_type, data)| {
+                let model =
+                    loot::load_loot_model_assets(asset_server, meshes, materials, &data.model);
+                (*ammo_type, loot_asset(model, data.rarity))
+            })
+            .collect();
+
+        // the rest of the loot shares a mesh per category
+        let throwable_mesh = loot::load_throwable_assets(meshes, materials);
+        self.throwable_loot = loot_data
+            .throwable
+            .iter()
+            .map(|(throwable_type, data)| {
+                let model = LootModelAsset::Mesh(throwable_mesh.clone());
+                (*throwable_type, loot_asset(model, data.rarity))
+            })
+            .collect();
+        let consumable_mesh = loot::load_consumable_assets(meshes, materials);
+        self.consumable_loot = loot_data
+            .consumable
+            .iter()
+            .map(|(consumable_type, data)| {
+                let model = LootModelAsset::Mesh(consumable_mesh.clone());
+                (*consumable_type, loot_asset(model, data.rarity))
+            })
+            .collect();
+        let armor_mesh = loot::load_armor_assets(meshes, materials);
+        self.armor_loot = loot_data
+            .armor
+            .iter()
+            .map(|(armor_type, data)| {
+                let model = LootModelAsset::Mesh(armor_mesh.clone());
+                (*armor_type, loot_asset(model, data.rarity))
+            })
+            .collect();
+        let backpack_mesh = loot::load_backpack_assets(meshes, materials);
+        self.backpack_loot = loot_data
+            .backpack
+            .iter()
+            .map(|(backpack_type, data)| {
+                let model = LootModelAsset::Mesh(backpack_mesh.clone());
+                (*backpack_type, loot_asset(model, data.rarity))
+            })
+            .collect();
 
         // projectiles
-        self.bullet_mesh = projectile::load_bullet_assets(meshes, materials);
+        self.bullet_mesh = projectile::load_projectile_assets(
+            meshes,
+            materials,
+            projectile_datum,
+            data::ProjectileType::Bullet,
+        );
+        self.rocket_mesh = projectile::load_projectile_assets(
+            meshes,
+            materials,
+            projectile_datum,
+            data::ProjectileType::Rocket,
+        );
+        self.grenade_mesh = projectile::load_projectile_assets(
+            meshes,
+            materials,
+            projectile_datum,
+            data::ProjectileType::Grenade,
+        );
     }
 
     pub fn gen_floor_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
@@ -86,24 +182,34 @@ impl GameAssets {
         self.crate_mesh.gen_components()
     }
 
-    pub fn gen_weapon_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
-        self.weapon_mesh.gen_components()
-    }
-
-    pub fn gen_ammo_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
-        self.ammo_mesh.gen_components()
-    }
-
-    pub fn gen_throwable_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
-        self.throwable_mesh.gen_components()
-    }
-
-    pub fn gen_consumable_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
-        self.consumable_mesh.gen_components()
+    pub fn get_loot_asset(&self, item: &inventory::InventoryItem) -> &LootAsset {
+        match item {
+            inventory::InventoryItem::Weapon(weapon_type, _) => {
+                self.weapon_loot.get(weapon_type).unwrap()
+            }
+            inventory::InventoryItem::Ammo(ammo_type, _) => self.ammo_loot.get(ammo_type).unwrap(),
+            inventory::InventoryItem::Throwable(throwable_type) => {
+                self.throwable_loot.get(throwable_type).unwrap()
+            }
+            inventory::InventoryItem::Consumable(consumable_type, _) => {
+                self.consumable_loot.get(consumable_type).unwrap()
+            }
+            inventory::InventoryItem::Armor(armor_type) => self.armor_loot.get(armor_type).unwrap(),
+            inventory::InventoryItem::Backpack(backpack_type) => {
+                self.backpack_loot.get(backpack_type).unwrap()
+            }
+        }
     }
 
-    pub fn gen_bullet_mesh_components(&self) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
-        self.bullet_mesh.gen_components()
+    pub fn gen_projectile_mesh_components(
+        &self,
+        projectile_type: data::ProjectileType,
+    ) -> (Mesh3d, MeshMaterial3d<StandardMaterial>) {
+        match projectile_type {
+            data::ProjectileType::Bullet => self.bullet_mesh.gen_components(),
+            data::ProjectileType::Rocket => self.rocket_mesh.gen_components(),
+            data::ProjectileType::Grenade => self.grenade_mesh.gen_components(),
+        }
     }
 }
 
@@ -113,6 +219,8 @@ pub fn load_assets(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    projectile_datum: Res<data::ProjectileDataSource>,
+    loot_data: loot::LootData,
 ) {
     let mut assets = GameAssets::default();
     assets.load(
@@ -121,6 +229,8 @@ pub fn load_assets(
         &mut meshes,
         &mut materials,
         &mut animation_graphs,
+        &projectile_datum,
+        &loot_data,
     );
 
     // these would be part of the scene asset
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+
+use crate::{AppState, data, health, input, inventory, player};
+
+// channeling a consumable slows the player down
+pub const CHANNEL_MOVE_SPEED_MODIFIER: f32 = 0.5;
+
+#[derive(Debug, Component)]
+pub struct Channeling {
+    pub consumable_type: data::ConsumableType,
+    timer: Timer,
+}
+
+impl Channeling {
+    pub fn get_progress(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct SpeedBoost {
+    multiplier: f32,
+    timer: Timer,
+}
+
+impl SpeedBoost {
+    pub fn get_multiplier(&self) -> f32 {
+        self.multiplier
+    }
+}
+
+#[derive(Debug, Event)]
+pub struct UseConsumableEvent {
+    pub consumable_type: data::ConsumableType,
+}
+
+#[derive(Debug)]
+pub struct ConsumablePlugin;
+
+impl Plugin for ConsumablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_channeling, update_speed_boosts)
+                .after(input::InputSet)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_observer(on_use_consumable)
+        .add_observer(interrupt_channeling::<health::DamageTakenEvent>)
+        .add_observer(interrupt_channeling::<health::DownedEvent>)
+        .add_observer(interrupt_channeling::<inventory::WeaponSlotChangedEvent>);
+    }
+}
+
+fn on_use_consumable(
+    trigger: Trigger<UseConsumableEvent>,
+    mut commands: Commands,
+    datum: Res<data::ConsumableDataSource>,
+    player_query: Query<
+        (
+            &inventory::Inventory,
+            &health::Health,
+            Option<&health::Shield>,
+            Has<Channeling>,
+        ),
+        Without<health::Downed>,
+    >,
+) {
+    let entity = trigger.entity();
+    let Ok((inventory, health, shield, channeling)) = player_query.get(entity) else {
+        return;
+    };
+
+    if channeling {
+        return;
+    }
+
+    if !inventory.has_consumable(trigger.consumable_type) {
+        warn!("no {} to use", trigger.consumable_type);
+        return;
+    }
+
+    let data = datum.get(&trigger.consumable_type).unwrap();
+
+    // don't waste consumables that won't do anything
+    let useful = match data.effect {
+        data::ConsumableEffect::Heal(_) => !health.is_full(),
+        data::ConsumableEffect::Shield(_) => shield.is_some_and(|shield| !shield.is_full()),
+        data::ConsumableEffect::SpeedBoost { .. } => true,
+    };
+    if !useful {
+        info!("{} wouldn't do anything", data.name);
+        return;
+    }
+
+    info!("{} using {}", entity, data.name);
+
+    commands.entity(entity).insert(Channeling {
+        consumable_type: trigger.consumable_type,
+        timer: Timer::from_seconds(data.channel_time, TimerMode::Once),
+    });
+}
+
+fn update_channeling(
+    mut commands: Commands,
+    time: Res<Time>,
+    input_state: Res<input::InputState>,
+    datum: Res<data::ConsumableDataSource>,
+    mut channeling_query: Query<(
+        Entity,
+        &mut Channeling,
+        &mut inventory::Inventory,
+        Has<player::LocalPlayer>,
+    )>,
+) {
+    for (entity, mut channeling, mut inventory, local) in channeling_query.iter_mut() {
+        // firing interrupts the channel
+        if local && input_state.firing {
+            info!(
+                "{} interrupted using {}",
+                entity, channeling.consumable_type
+            );
+            commands.entity(entity).remove::<Channeling>();
+            continue;
+        }
+
+        channeling.timer.tick(time.delta());
+        if !channeling.timer.just_finished() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<Channeling>();
+
+        if !inventory.take_consumable(channeling.consumable_type) {
+            warn!("{} no longer has {}", entity, channeling.consumable_type);
+            continue;
+        }
+
+        let data = datum.get(&channeling.consumable_type).unwrap();
+        info!("{} used {}", entity, data.name);
+
+        match data.effect {
+            data::ConsumableEffect::Heal(amount) => {
+                commands.trigger_targets(health::HealEvent { amount }, entity);
+            }
+            data::ConsumableEffect::Shield(amount) => {
+                commands.trigger_targets(health::RechargeShieldEvent { amount }, entity);
+            }
+            data::ConsumableEffect::SpeedBoost {
+                multiplier,
+                duration,
+            } => {
+                commands.entity(entity).insert(SpeedBoost {
+                    multiplier,
+                    timer: Timer::from_seconds(duration, TimerMode::Once),
+                });
+            }
+        }
+    }
+}
+
+fn interrupt_channeling<E: Event>(
+    trigger: Trigger<E>,
+    mut commands: Commands,
+    channeling_query: Query<&Channeling>,
+) {
+    let entity = trigger.entity();
+    let Ok(channeling) = channeling_query.get(entity) else {
+        return;
+    };
+
+    info!(
+        "{} interrupted using {}",
+        entity, channeling.consumable_type
+    );
+    commands.entity(entity).remove::<Channeling>();
+}
+
+fn update_speed_boosts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boost_query: Query<(Entity, &mut SpeedBoost)>,
+) {
+    for (entity, mut boost) in boost_query.iter_mut() {
+        boost.timer.tick(time.delta());
+        if boost.timer.just_finished() {
+            info!("{} speed boost expired", entity);
+            commands.entity(entity).remove::<SpeedBoost>();
+        }
+    }
+}
+
+// picks whatever heals the most of what's missing
+// health first, then shield
+pub fn choose_quick_heal(
+    inventory: &inventory::Inventory,
+    datum: &data::ConsumableDatum,
+    health: &health::Health,
+    shield: Option<&health::Shield>,
+) -> Option<data::ConsumableType> {
+    let missing_health = health.max() - health.current();
+    let missing_shield = shield
+        .map(|shield| shield.capacity() - shield.current())
+        .unwrap_or_default();
+
+    // prefer the smallest consumable that covers what's missing
+    // otherwise the biggest one we have
+    let choose = |missing: usize, heal: bool| {
+        let candidates = inventory
+            .get_items()
+            .iter()
+            .filter_map(|item| {
+                let inventory::InventoryItem::Consumable(consumable_type, _) = item else {
+                    return None;
+                };
+
+                match datum.get(consumable_type).unwrap().effect {
+                    data::ConsumableEffect::Heal(amount) if heal => {
+                        Some((*consumable_type, amount))
+                    }
+                    data::ConsumableEffect::Shield(amount) if !heal => {
+                        Some((*consumable_type, amount))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        candidates
+            .iter()
+            .filter(|(_, amount)| *amount >= missing)
+            .min_by_key(|(_, amount)| *amount)
+            .or_else(|| candidates.iter().max_by_key(|(_, amount)| *amount))
+            .map(|(consumable_type, _)| *consumable_type)
+    };
+
+    let quick_heal = if missing_health > 0 {
+        choose(missing_health, true)
+    } else {
+        None
+    };
+
+    quick_heal.or_else(|| {
+        if missing_shield > 0 {
+            choose(missing_shield, false)
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,454 @@
+use std::ops::RangeInclusive;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{AppState, assets, health, input, interactables, inventory, player, ui};
+
+// how many items get rolled into each container
+pub const CONTAINER_LOOT_COUNT: RangeInclusive<usize> = 1..=3;
+
+const DEATH_BOX_HEIGHT: f32 = 0.5;
+
+// containers keep their items until someone takes them
+// so everyone searching it sees the same contents
+#[derive(Debug, Default, Component, Reflect)]
+pub struct Container {
+    items: Vec<inventory::InventoryItem>,
+}
+
+impl Container {
+    pub fn get_items(&self) -> &Vec<inventory::InventoryItem> {
+        &self.items
+    }
+
+    pub fn add_item(&mut self, item: inventory::InventoryItem) {
+        self.items.push(item);
+    }
+
+    // everything the inventory is carrying, weapons included
+    pub fn from_inventory(inventory: &inventory::Inventory) -> Self {
+        let weapons = inventory
+            .get_primary_weapon()
+            .into_iter()
+            .chain(inventory.get_secondary_weapon())
+            .map(|weapon| inventory::InventoryItem::Weapon(weapon.r#type, weapon.ammo_count));
+        let backpack = inventory
+            .get_backpack()
+            .map(inventory::InventoryItem::Backpack);
+
+        Self {
+            items: weapons
+                .chain(backpack)
+                .chain(inventory.get_items().iter().copied())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Resource, Reflect)]
+pub struct DeathBoxSettings {
+    // how long a death box sticks around before despawning
+    pub lifetime: f32,
+}
+
+impl Default for DeathBoxSettings {
+    fn default() -> Self {
+        Self { lifetime: 60.0 }
+    }
+}
+
+#[derive(Debug, Component)]
+#[require(Container)]
+pub struct DeathBox(Timer);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TransferSource {
+    #[default]
+    Container,
+    Inventory,
+}
+
+impl TransferSource {
+    fn other(&self) -> Self {
+        match self {
+            Self::Container => Self::Inventory,
+            Self::Inventory => Self::Container,
+        }
+    }
+}
+
+// the player has a container open
+#[derive(Debug, Component)]
+pub struct Searching {
+    pub container: Entity,
+    source: TransferSource,
+    selected: usize,
+}
+
+impl Searching {
+    pub fn new(container: Entity) -> Self {
+        Self {
+            container,
+            source: TransferSource::default(),
+            selected: 0,
+        }
+    }
+}
+
+#[derive(Debug, Event)]
+pub struct TransferItemEvent {
+    pub source: TransferSource,
+    pub slot: usize,
+}
+
+#[derive(Debug, Component)]
+struct ContainerUi;
+
+#[derive(Debug, Component)]
+struct ContainerNameLabel;
+
+#[derive(Debug, Component)]
+struct ContainerItemsLabel;
+
+#[derive(Debug, Component)]
+struct InventoryItemsLabel;
+
+#[derive(Debug)]
+pub struct ContainerPlugin;
+
+impl Plugin for ContainerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (update_searching, update_container_ui).chain(),
+                update_death_boxes,
+            )
+                .after(input::InputSet)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_observer(on_transfer_item)
+        .add_observer(on_search_start)
+        .add_observer(on_search_end)
+        .add_observer(on_downed)
+        .init_resource::<DeathBoxSettings>()
+        .register_type::<DeathBoxSettings>()
+        .register_type::<Container>();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_searching(
+    mut commands: Commands,
+    mut evr_navigate: EventReader<input::NavigateInputEvent>,
+    mut evr_confirm: EventReader<input::ConfirmInputEvent>,
+    mut player_query: Query<
+        (
+            Entity,
+            &CollidingEntities,
+            &inventory::Inventory,
+            &mut Searching,
+        ),
+        With<player::LocalPlayer>,
+    >,
+    interactable_query: Query<(&interactables::InteractableType, &Parent)>,
+    container_query: Query<&Container>,
+) {
+    let Ok((player, colliding_entities, inventory, mut searching)) = player_query.get_single_mut()
+    else {
+        evr_navigate.clear();
+        evr_confirm.clear();
+        return;
+    };
+
+    let in_range = colliding_entities.iter().any(|entity| {
+        interactable_query
+            .get(*entity)
+            .is_ok_and(|(interactable, parent)| {
+                parent.get() == searching.container
+                    && *interactable == interactables::InteractableType::Container
+            })
+    });
+
+    let container = container_query.get(searching.container);
+    if !in_range || container.is_err() {
+        info!("close container {}", searching.container);
+        commands.entity(player).remove::<Searching>();
+        evr_navigate.clear();
+        evr_confirm.clear();
+        return;
+    }
+    let container = container.unwrap();
+
+    for navigate in evr_navigate.read() {
+        if navigate.x != 0 {
+            searching.source = searching.source.other();
+            searching.selected = 0;
+        }
+        searching.selected = searching
+            .selected
+            .saturating_add_signed(navigate.y as isize);
+    }
+
+    let count = match searching.source {
+        TransferSource::Container => container.get_items().len(),
+        TransferSource::Inventory => inventory.get_items().len(),
+    };
+    searching.selected = searching.selected.min(count.saturating_sub(1));
+
+    if evr_confirm.is_empty() {
+        return;
+    }
+    evr_confirm.clear();
+
+    if searching.selected < count {
+        commands.trigger_targets(
+            TransferItemEvent {
+                source: searching.source,
+                slot: searching.selected,
+            },
+            player,
+        );
+    }
+}
+
+fn format_items(items: &[inventory::InventoryItem], selected: Option<usize>) -> String {
+    if items.is_empty() {
+        return "(empty)".to_owned();
+    }
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let cursor = if Some(idx) == selected { ">" } else { " " };
+            let name = match item {
+                inventory::InventoryItem::Weapon(weapon_type, _) => weapon_type.to_string(),
+                inventory::InventoryItem::Ammo(ammo_type, count) => {
+                    format!("{} Ammo: {}", ammo_type, count)
+                }
+                inventory::InventoryItem::Throwable(throwable_type) => throwable_type.to_string(),
+                inventory::InventoryItem::Consumable(consumable_type, count) => {
+                    format!("{}: {}", consumable_type, count)
+                }
+                inventory::InventoryItem::Armor(armor_type) => format!("{} Armor", armor_type),
+                inventory::InventoryItem::Backpack(backpack_type) => {
+                    format!("{} Backpack", backpack_type)
+                }
+            };
+            format!("{} {}\n", cursor, name)
+        })
+        .collect()
+}
+
+fn update_container_ui(
+    player_query: Query<(&inventory::Inventory, &Searching), With<player::LocalPlayer>>,
+    container_query: Query<(&Container, Option<&Name>)>,
+    mut label_set: ParamSet<(
+        Query<&mut Text, With<ContainerItemsLabel>>,
+        Query<&mut Text, With<InventoryItemsLabel>>,
+        Query<&mut Text, With<ContainerNameLabel>>,
+    )>,
+) {
+    let Ok((inventory, searching)) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok((container, name)) = container_query.get(searching.container) else {
+        return;
+    };
+
+    if let Ok(mut label) = label_set.p2().get_single_mut() {
+        label.0 = format!("{}:", name.map(|name| name.as_str()).unwrap_or("Container"));
+    }
+
+    // other players can change the container so this is refreshed every frame
+    if let Ok(mut label) = label_set.p0().get_single_mut() {
+        label.0 = format_items(
+            container.get_items(),
+            (searching.source == TransferSource::Container).then_some(searching.selected),
+        );
+    }
+
+    if let Ok(mut label) = label_set.p1().get_single_mut() {
+        label.0 = format_items(
+            inventory.get_items(),
+            (searching.source == TransferSource::Inventory).then_some(searching.selected),
+        );
+    }
+}
+
+fn on_transfer_item(
+    trigger: Trigger<TransferItemEvent>,
+    mut commands: Commands,
+    pickup_data: interactables::PickupData,
+    mut player_query: Query<(
+        &Searching,
+        &mut inventory::Inventory,
+        &mut health::Armor,
+        &GlobalTransform,
+    )>,
+    mut container_query: Query<&mut Container>,
+) {
+    let Ok((searching, mut inventory, mut armor, global_transform)) =
+        player_query.get_mut(trigger.entity())
+    else {
+        return;
+    };
+
+    let Ok(mut container) = container_query.get_mut(searching.container) else {
+        return;
+    };
+
+    match trigger.source {
+        TransferSource::Container => {
+            let Some(item) = container.items.get(trigger.slot).copied() else {
+                warn!("invalid container slot {}", trigger.slot);
+                return;
+            };
+
+            let result = interactables::pickup_item(
+                &mut commands,
+                &pickup_data,
+                item,
+                &mut inventory,
+                &mut armor,
+                global_transform,
+            );
+
+            let Err(rejection) = result else {
+                info!("took {:?} from container", item);
+                container.items.remove(trigger.slot);
+                return;
+            };
+
+            // partial transfers leave the remainder in the container
+            container.items[trigger.slot] = rejection.remaining;
+
+            commands.trigger_targets(
+                inventory::PickupRejectedEvent {
+                    item: rejection.remaining,
+                    reason: rejection.reason,
+                },
+                trigger.entity(),
+            );
+        }
+        TransferSource::Inventory => {
+            let Some(item) = inventory.take_item(trigger.slot, None) else {
+                return;
+            };
+
+            info!("put {:?} in container", item);
+            container.add_item(item);
+        }
+    }
+}
+
+fn update_death_boxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut death_box_query: Query<(Entity, &mut DeathBox)>,
+) {
+    for (entity, mut death_box) in death_box_query.iter_mut() {
+        death_box.0.tick(time.delta());
+        if death_box.0.just_finished() {
+            info!("despawn death box {}", entity);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn on_search_start(
+    trigger: Trigger<OnAdd, Searching>,
+    mut commands: Commands,
+    player_query: Query<(), With<player::LocalPlayer>>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    spawn_container_ui(&mut commands);
+}
+
+fn on_search_end(
+    trigger: Trigger<OnRemove, Searching>,
+    mut commands: Commands,
+    player_query: Query<(), With<player::LocalPlayer>>,
+    ui_query: Query<Entity, With<ContainerUi>>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn on_downed(trigger: Trigger<health::DownedEvent>, mut commands: Commands) {
+    commands.entity(trigger.entity()).remove::<Searching>();
+}
+
+fn spawn_container_ui(commands: &mut Commands) {
+    ui::spawn_canvas(commands, "Container", false)
+        .insert(ContainerUi)
+        .with_children(|parent| {
+            ui::spawn_vbox_at(parent, (Val::Percent(30.0), Val::Percent(30.0))).with_children(
+                |parent| {
+                    ui::spawn_label(parent, "Container:").insert(ContainerNameLabel);
+                    ui::spawn_label(parent, "").insert(ContainerItemsLabel);
+                },
+            );
+
+            ui::spawn_vbox_at(parent, (Val::Percent(55.0), Val::Percent(30.0))).with_children(
+                |parent| {
+                    ui::spawn_label(parent, "Inventory:");
+                    ui::spawn_label(parent, "").insert(InventoryItemsLabel);
+                },
+            );
+
+            ui::spawn_vbox_at(parent, (Val::Percent(30.0), Val::Percent(70.0))).with_children(
+                |parent| {
+                    ui::spawn_label(
+                        parent,
+                        "Arrows / D-Pad: select, Enter / A: transfer, Interact: close",
+                    );
+                },
+            );
+        });
+}
+
+// drops everything the dead player was carrying where they died
+pub fn spawn_death_box(
+    commands: &mut Commands,
+    game_assets: &assets::GameAssets,
+    settings: &DeathBoxSettings,
+    victim: &str,
+    inventory: &inventory::Inventory,
+    position: Vec3,
+) {
+    let container = Container::from_inventory(inventory);
+    if container.get_items().is_empty() {
+        info!("{} had nothing to drop", victim);
+        return;
+    }
+
+    info!(
+        "spawn death box for {} with {:?}",
+        victim,
+        container.get_items()
+    );
+
+    commands
+        .spawn((
+            game_assets.gen_box_mesh_components(),
+            Transform::from_xyz(position.x, DEATH_BOX_HEIGHT, position.z),
+            Name::new(format!("Death Box - {}", victim)),
+            container,
+            DeathBox(Timer::from_seconds(settings.lifetime, TimerMode::Once)),
+        ))
+        .with_children(|parent| {
+            interactables::spawn_interactable(parent, interactables::InteractableType::Container);
+        });
+}
@@ -2,18 +2,49 @@
 
 use std::collections::HashMap;
 
-use bevy::prelude::*;
+use bevy::{color::palettes::css, prelude::*};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+// what an item looks like lying on the ground
+#[derive(Debug, Clone)]
+pub enum LootModel {
+    // path to a glTF scene
+    Scene(String),
+    Capsule {
+        radius: f32,
+        length: f32,
+        color: Srgba,
+    },
+    Cuboid {
+        size: Vec3,
+        color: Srgba,
+    },
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
 pub enum AmmoType {
     Light,
+    Rocket,
 }
 
 #[derive(Debug)]
 pub struct AmmoData {
     pub name: String,
+    pub rarity: Rarity,
+    pub model: LootModel,
     pub loot_size: usize,
     pub stack_size: usize,
+    // per round
+    pub weight: f32,
+    // per stack
+    pub footprint: UVec2,
 }
 
 pub type AmmoDatum = HashMap<AmmoType, AmmoData>;
@@ -22,12 +53,363 @@ pub type AmmoDatum = HashMap<AmmoType, AmmoData>;
 pub struct AmmoDataSource(AmmoDatum);
 
 fn register_ammo_data(commands: &mut Commands) {
-    commands.insert_resource(AmmoDataSource(AmmoDatum::from([(
-        AmmoType::Light,
-        AmmoData {
-            name: "Light".to_owned(),
-            loot_size: 20,
-            stack_size: 50,
+    commands.insert_resource(AmmoDataSource(AmmoDatum::from([
+        (
+            AmmoType::Light,
+            AmmoData {
+                name: "Light".to_owned(),
+                rarity: Rarity::Common,
+                model: LootModel::Cuboid {
+                    size: Vec3::splat(0.5),
+                    color: css::GREEN_YELLOW,
+                },
+                loot_size: 20,
+                stack_size: 50,
+                weight: 0.01,
+                footprint: UVec2::new(1, 1),
+            },
+        ),
+        (
+            AmmoType::Rocket,
+            AmmoData {
+                name: "Rocket".to_owned(),
+                rarity: Rarity::Rare,
+                model: LootModel::Cuboid {
+                    size: Vec3::new(0.3, 0.3, 0.6),
+                    color: css::ORANGE_RED,
+                },
+                loot_size: 2,
+                stack_size: 6,
+                weight: 0.5,
+                footprint: UVec2::new(1, 2),
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum ArmorType {
+    Light,
+    Heavy,
+}
+
+#[derive(Debug)]
+pub struct ArmorData {
+    pub name: String,
+    pub rarity: Rarity,
+    pub capacity: usize,
+    // fraction of incoming damage the armor soaks up
+    pub absorption: f32,
+}
+
+pub type ArmorDatum = HashMap<ArmorType, ArmorData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct ArmorDataSource(ArmorDatum);
+
+fn register_armor_data(commands: &mut Commands) {
+    commands.insert_resource(ArmorDataSource(ArmorDatum::from([
+        (
+            ArmorType::Light,
+            ArmorData {
+                name: "Light Armor".to_owned(),
+                rarity: Rarity::Uncommon,
+                capacity: 50,
+                absorption: 0.3,
+            },
+        ),
+        (
+            ArmorType::Heavy,
+            ArmorData {
+                name: "Heavy Armor".to_owned(),
+                rarity: Rarity::Rare,
+                capacity: 100,
+                absorption: 0.6,
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum BackpackType {
+    Small,
+    Medium,
+    Large,
+}
+
+// how much capacity the backpack adds for each capacity model
+#[derive(Debug)]
+pub struct BackpackData {
+    pub name: String,
+    pub rarity: Rarity,
+    pub slots: usize,
+    pub weight: f32,
+    pub grid_rows: u32,
+}
+
+pub type BackpackDatum = HashMap<BackpackType, BackpackData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct BackpackDataSource(BackpackDatum);
+
+fn register_backpack_data(commands: &mut Commands) {
+    commands.insert_resource(BackpackDataSource(BackpackDatum::from([
+        (
+            BackpackType::Small,
+            BackpackData {
+                name: "Small Backpack".to_owned(),
+                rarity: Rarity::Common,
+                slots: 2,
+                weight: 5.0,
+                grid_rows: 1,
+            },
+        ),
+        (
+            BackpackType::Medium,
+            BackpackData {
+                name: "Medium Backpack".to_owned(),
+                rarity: Rarity::Uncommon,
+                slots: 4,
+                weight: 10.0,
+                grid_rows: 2,
+            },
+        ),
+        (
+            BackpackType::Large,
+            BackpackData {
+                name: "Large Backpack".to_owned(),
+                rarity: Rarity::Rare,
+                slots: 6,
+                weight: 15.0,
+                grid_rows: 3,
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum ConsumableType {
+    Bandage,
+    MedKit,
+    ShieldCell,
+    Stim,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum ConsumableEffect {
+    Heal(usize),
+    Shield(usize),
+    // multiplies move speed for the duration
+    SpeedBoost { multiplier: f32, duration: f32 },
+}
+
+#[derive(Debug)]
+pub struct ConsumableData {
+    pub name: String,
+    pub rarity: Rarity,
+    pub loot_size: usize,
+    pub stack_size: usize,
+    // per item
+    pub weight: f32,
+    // per stack
+    pub footprint: UVec2,
+    pub channel_time: f32,
+    pub effect: ConsumableEffect,
+}
+
+pub type ConsumableDatum = HashMap<ConsumableType, ConsumableData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct ConsumableDataSource(ConsumableDatum);
+
+fn register_consumable_data(commands: &mut Commands) {
+    commands.insert_resource(ConsumableDataSource(ConsumableDatum::from([
+        (
+            ConsumableType::Bandage,
+            ConsumableData {
+                name: "Bandage".to_owned(),
+                rarity: Rarity::Common,
+                loot_size: 3,
+                stack_size: 10,
+                weight: 0.1,
+                footprint: UVec2::new(1, 1),
+                channel_time: 2.0,
+                effect: ConsumableEffect::Heal(15),
+            },
+        ),
+        (
+            ConsumableType::MedKit,
+            ConsumableData {
+                name: "Med Kit".to_owned(),
+                rarity: Rarity::Rare,
+                loot_size: 1,
+                stack_size: 3,
+                weight: 1.0,
+                footprint: UVec2::new(2, 2),
+                channel_time: 6.0,
+                effect: ConsumableEffect::Heal(100),
+            },
+        ),
+        (
+            ConsumableType::ShieldCell,
+            ConsumableData {
+                name: "Shield Cell".to_owned(),
+                rarity: Rarity::Uncommon,
+                loot_size: 2,
+                stack_size: 6,
+                weight: 0.3,
+                footprint: UVec2::new(1, 1),
+                channel_time: 3.0,
+                effect: ConsumableEffect::Shield(25),
+            },
+        ),
+        (
+            ConsumableType::Stim,
+            ConsumableData {
+                name: "Stim".to_owned(),
+                rarity: Rarity::Uncommon,
+                loot_size: 1,
+                stack_size: 3,
+                weight: 0.2,
+                footprint: UVec2::new(1, 2),
+                channel_time: 1.0,
+                effect: ConsumableEffect::SpeedBoost {
+                    multiplier: 1.3,
+                    duration: 10.0,
+                },
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum ProjectileType {
+    Bullet,
+    Rocket,
+    Grenade,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ExplosionData {
+    pub radius: f32,
+    pub damage: usize,
+    // fraction of the damage / impulse applied at the edge of the radius
+    pub falloff: f32,
+    pub impulse: f32,
+}
+
+// projectiles with ballistic data tumble and bounce off things
+// instead of colliding on impact
+#[derive(Debug, Copy, Clone)]
+pub struct BallisticData {
+    pub restitution: f32,
+    pub friction: f32,
+    pub angular_damping: f32,
+}
+
+#[derive(Debug)]
+pub struct ProjectileData {
+    pub name: String,
+    pub radius: f32,
+    pub mass: f32,
+    pub gravity_scale: f32,
+    pub fuse: Option<f32>,
+    pub explosion: Option<ExplosionData>,
+    pub ballistic: Option<BallisticData>,
+}
+
+pub type ProjectileDatum = HashMap<ProjectileType, ProjectileData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct ProjectileDataSource(ProjectileDatum);
+
+fn register_projectile_data(commands: &mut Commands) {
+    commands.insert_resource(ProjectileDataSource(ProjectileDatum::from([
+        (
+            ProjectileType::Bullet,
+            ProjectileData {
+                name: "Bullet".to_owned(),
+                radius: 0.1,
+                mass: 0.005,
+                gravity_scale: 0.0,
+                fuse: None,
+                explosion: None,
+                ballistic: None,
+            },
+        ),
+        (
+            ProjectileType::Rocket,
+            ProjectileData {
+                name: "Rocket".to_owned(),
+                radius: 0.15,
+                mass: 0.5,
+                gravity_scale: 0.0,
+                fuse: Some(3.0),
+                explosion: Some(ExplosionData {
+                    radius: 4.0,
+                    damage: 80,
+                    falloff: 0.25,
+                    impulse: 50.0,
+                }),
+                ballistic: None,
+            },
+        ),
+        (
+            ProjectileType::Grenade,
+            ProjectileData {
+                name: "Grenade".to_owned(),
+                radius: 0.2,
+                mass: 0.4,
+                gravity_scale: 1.0,
+                fuse: Some(3.0),
+                explosion: Some(ExplosionData {
+                    radius: 3.0,
+                    damage: 60,
+                    falloff: 0.25,
+                    impulse: 30.0,
+                }),
+                ballistic: Some(BallisticData {
+                    restitution: 0.4,
+                    friction: 0.8,
+                    angular_damping: 2.0,
+                }),
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum ThrowableType {
+    Grenade,
+}
+
+#[derive(Debug)]
+pub struct ThrowableData {
+    pub name: String,
+    pub rarity: Rarity,
+    pub projectile_type: ProjectileType,
+    pub max_range: f32,
+    pub throw_angle: f32,
+    pub weight: f32,
+    pub footprint: UVec2,
+}
+
+pub type ThrowableDatum = HashMap<ThrowableType, ThrowableData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct ThrowableDataSource(ThrowableDatum);
+
+fn register_throwable_data(commands: &mut Commands) {
+    commands.insert_resource(ThrowableDataSource(ThrowableDatum::from([(
+        ThrowableType::Grenade,
+        ThrowableData {
+            name: "Grenade".to_owned(),
+            rarity: Rarity::Uncommon,
+            projectile_type: ProjectileType::Grenade,
+            max_range: 15.0,
+            throw_angle: 45.0_f32.to_radians(),
+            weight: 0.5,
+            footprint: UVec2::new(1, 1),
         },
     )])));
 }
@@ -35,6 +417,14 @@ fn register_ammo_data(commands: &mut Commands) {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
 pub enum WeaponType {
     Pistol,
+    RocketLauncher,
+}
+
+// ordered by severity so the worst zone wins when several are hit at once
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, strum::Display)]
+pub enum HitZone {
+    Body,
+    Head,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::Display)]
@@ -47,12 +437,29 @@ pub enum WeaponFireMode {
 #[derive(Debug)]
 pub struct WeaponData {
     pub name: String,
+    pub rarity: Rarity,
+    pub model: LootModel,
     pub ammo_type: AmmoType,
     pub magazine_size: usize,
     pub fire_mode: WeaponFireMode,
     pub fire_rate: f32,
+    pub projectile_type: ProjectileType,
     pub projectile_speed: f32,
+    // max degrees a shot can stray to either side of where it's aimed
+    pub spread: f32,
     pub damage: usize,
+    pub hit_zone_multipliers: HashMap<HitZone, f32>,
+}
+
+impl WeaponData {
+    pub fn get_damage(&self, hit_zone: Option<HitZone>) -> usize {
+        let multiplier = hit_zone
+            .and_then(|hit_zone| self.hit_zone_multipliers.get(&hit_zone))
+            .copied()
+            .unwrap_or(1.0);
+
+        (self.damage as f32 * multiplier).round() as usize
+    }
 }
 
 pub type WeaponDatum = HashMap<WeaponType, WeaponData>;
@@ -61,21 +468,135 @@ pub type WeaponDatum = HashMap<WeaponType, WeaponData>;
 pub struct WeaponDataSource(WeaponDatum);
 
 fn register_weapon_data(commands: &mut Commands) {
-    commands.insert_resource(WeaponDataSource(WeaponDatum::from([(
-        WeaponType::Pistol,
-        WeaponData {
-            name: "Pistol".to_owned(),
-            ammo_type: AmmoType::Light,
-            magazine_size: 10,
-            fire_mode: WeaponFireMode::SemiAuto,
-            fire_rate: 0.25,
-            projectile_speed: 200.0,
-            damage: 10,
-        },
-    )])));
+    commands.insert_resource(WeaponDataSource(WeaponDatum::from([
+        (
+            WeaponType::Pistol,
+            WeaponData {
+                name: "Pistol".to_owned(),
+                rarity: Rarity::Common,
+                model: LootModel::Capsule {
+                    radius: 0.25,
+                    length: 0.5,
+                    color: css::DARK_RED,
+                },
+                ammo_type: AmmoType::Light,
+                magazine_size: 10,
+                fire_mode: WeaponFireMode::SemiAuto,
+                fire_rate: 0.25,
+                projectile_type: ProjectileType::Bullet,
+                projectile_speed: 200.0,
+                spread: 2.0,
+                damage: 10,
+                hit_zone_multipliers: HashMap::from([(HitZone::Body, 1.0), (HitZone::Head, 2.0)]),
+            },
+        ),
+        (
+            WeaponType::RocketLauncher,
+            WeaponData {
+                name: "Rocket Launcher".to_owned(),
+                rarity: Rarity::Epic,
+                model: LootModel::Capsule {
+                    radius: 0.3,
+                    length: 0.8,
+                    color: css::DARK_OLIVEGREEN,
+                },
+                ammo_type: AmmoType::Rocket,
+                magazine_size: 1,
+                fire_mode: WeaponFireMode::SemiAuto,
+                fire_rate: 1.5,
+                projectile_type: ProjectileType::Rocket,
+                projectile_speed: 30.0,
+                spread: 1.0,
+                // most of the damage comes from the explosion
+                damage: 20,
+                hit_zone_multipliers: HashMap::new(),
+            },
+        ),
+    ])));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumIter)]
+pub enum GameModeType {
+    Standard,
+    Survival,
+    Extraction,
+    Arena,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Reflect)]
+pub enum InventoryCapacity {
+    Slots(usize),
+    Weight(f32),
+    Grid(UVec2),
+}
+
+impl InventoryCapacity {
+    pub fn with_backpack(self, backpack: &BackpackData) -> Self {
+        match self {
+            Self::Slots(slots) => Self::Slots(slots + backpack.slots),
+            Self::Weight(weight) => Self::Weight(weight + backpack.weight),
+            Self::Grid(size) => Self::Grid(size + UVec2::new(0, backpack.grid_rows)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GameModeData {
+    pub name: String,
+    pub inventory_capacity: InventoryCapacity,
+    // ground loot is only spawned once if this is None
+    pub loot_respawn_interval: Option<f32>,
+}
+
+pub type GameModeDatum = HashMap<GameModeType, GameModeData>;
+
+#[derive(Debug, Deref, Resource)]
+pub struct GameModeDataSource(GameModeDatum);
+
+fn register_game_mode_data(commands: &mut Commands) {
+    commands.insert_resource(GameModeDataSource(GameModeDatum::from([
+        (
+            GameModeType::Standard,
+            GameModeData {
+                name: "Standard".to_owned(),
+                inventory_capacity: InventoryCapacity::Slots(10),
+                loot_respawn_interval: None,
+            },
+        ),
+        (
+            GameModeType::Survival,
+            GameModeData {
+                name: "Survival".to_owned(),
+                inventory_capacity: InventoryCapacity::Weight(10.0),
+                loot_respawn_interval: None,
+            },
+        ),
+        (
+            GameModeType::Extraction,
+            GameModeData {
+                name: "Extraction".to_owned(),
+                inventory_capacity: InventoryCapacity::Grid(UVec2::new(6, 4)),
+                loot_respawn_interval: None,
+            },
+        ),
+        (
+            GameModeType::Arena,
+            GameModeData {
+                name: "Arena".to_owned(),
+                inventory_capacity: InventoryCapacity::Slots(10),
+                loot_respawn_interval: Some(30.0),
+            },
+        ),
+    ])));
 }
 
 pub fn register_data(commands: &mut Commands) {
     register_ammo_data(commands);
+    register_armor_data(commands);
+    register_backpack_data(commands);
+    register_consumable_data(commands);
+    register_game_mode_data(commands);
+    register_projectile_data(commands);
+    register_throwable_data(commands);
     register_weapon_data(commands);
 }
@@ -129,7 +129,9 @@ fn game_debug_ui(
     cursor_query: Query<&Node, With<cursor::Cursor>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<camera::MainCamera>>,
 ) {
-    let player_global_transform = player_query.single();
+    let Ok(player_global_transform) = player_query.get_single() else {
+        return;
+    };
     let cursor_node = cursor_query.single();
     let (camera, camera_global_transform) = camera_query.single();
 
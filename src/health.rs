@@ -0,0 +1,638 @@
+use bevy::prelude::*;
+
+use crate::data;
+
+#[derive(Debug, Deref, Component, Reflect)]
+pub struct HitBox(pub data::HitZone);
+
+// ignores all incoming damage
+#[derive(Debug, Default, Component)]
+pub struct Invulnerable;
+
+#[derive(Debug, Component, Reflect)]
+pub struct Health {
+    current: usize,
+    max: usize,
+}
+
+impl Health {
+    pub fn new(max: usize) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    // returns the amount of damage actually taken
+    fn damage(&mut self, amount: usize) -> usize {
+        let amount = amount.min(self.current);
+        self.current -= amount;
+        amount
+    }
+
+    // returns the amount of health actually restored
+    fn heal(&mut self, amount: usize) -> usize {
+        let amount = amount.min(self.max - self.current);
+        self.current += amount;
+        amount
+    }
+}
+
+// entities that go down at zero health instead of dying outright
+#[derive(Debug, Component)]
+pub struct Downable {
+    bleed_out: f32,
+    revive_health: usize,
+}
+
+impl Downable {
+    pub fn new(bleed_out: f32, revive_health: usize) -> Self {
+        Self {
+            bleed_out,
+            revive_health,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Downed {
+    bleed_out_timer: Timer,
+
+    // whoever downed us gets credit if we bleed out
+    instigator: Entity,
+    weapon_type: Option<data::WeaponType>,
+}
+
+impl Downed {
+    pub fn get_bleed_out_remaining(&self) -> f32 {
+        self.bleed_out_timer.remaining_secs()
+    }
+}
+
+// absorbs all incoming damage until it breaks
+// and then regenerates after going a while without taking damage
+#[derive(Debug, Component, Reflect)]
+pub struct Shield {
+    current: usize,
+    capacity: usize,
+    absorption: f32,
+
+    regen_delay_timer: Timer,
+    regen_timer: Timer,
+}
+
+impl Shield {
+    pub fn new(capacity: usize, regen_delay: f32, regen_rate: f32) -> Self {
+        Self {
+            current: capacity,
+            capacity,
+            absorption: 1.0,
+            regen_delay_timer: Timer::from_seconds(regen_delay, TimerMode::Once),
+            regen_timer: Timer::from_seconds(1.0 / regen_rate, TimerMode::Repeating),
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.capacity
+    }
+
+    // returns the amount of shield actually restored
+    fn recharge(&mut self, amount: usize) -> usize {
+        let amount = amount.min(self.capacity - self.current);
+        self.current += amount;
+        amount
+    }
+
+    fn absorb(&mut self, remaining: &mut usize) -> usize {
+        self.regen_delay_timer.reset();
+
+        let absorbed = absorb(&mut self.current, self.absorption, remaining);
+        if absorbed > 0 {
+            info!("shield absorbed {} damage", absorbed);
+        }
+        absorbed
+    }
+}
+
+// soaks up part of the incoming damage and only refills from pickups
+#[derive(Debug, Default, Component, Reflect)]
+pub struct Armor {
+    r#type: Option<data::ArmorType>,
+    current: usize,
+    capacity: usize,
+    absorption: f32,
+}
+
+impl Armor {
+    pub fn get_type(&self) -> Option<data::ArmorType> {
+        self.r#type
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn equip(&mut self, armor_type: data::ArmorType, data: &data::ArmorData) {
+        info!("equip armor {}", data.name);
+
+        // TODO: drop the old armor
+        self.r#type = Some(armor_type);
+        self.current = data.capacity;
+        self.capacity = data.capacity;
+        self.absorption = data.absorption;
+    }
+
+    fn absorb(&mut self, remaining: &mut usize) -> usize {
+        let absorbed = absorb(&mut self.current, self.absorption, remaining);
+        if absorbed > 0 {
+            info!("armor absorbed {} damage", absorbed);
+        }
+        absorbed
+    }
+}
+
+fn absorb(current: &mut usize, absorption: f32, remaining: &mut usize) -> usize {
+    let absorbed = ((*remaining as f32 * absorption).round() as usize)
+        .min(*current)
+        .min(*remaining);
+
+    *current -= absorbed;
+    *remaining -= absorbed;
+
+    absorbed
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DamageBreakdown {
+    pub shield: usize,
+    pub armor: usize,
+    pub health: usize,
+    pub shield_broken: bool,
+}
+
+impl DamageBreakdown {
+    pub fn total(&self) -> usize {
+        self.shield + self.armor + self.health
+    }
+}
+
+#[derive(Debug, Event)]
+pub struct DamageEvent {
+    // the entity that dealt the damage (projectile, explosion, etc)
+    pub source: Entity,
+    // the entity responsible for the damage (usually a player)
+    pub instigator: Entity,
+    pub weapon_type: Option<data::WeaponType>,
+    pub hit_zone: Option<data::HitZone>,
+    pub amount: usize,
+}
+
+// incoming damage is split between shield, armor and health (in that order)
+#[derive(Debug, Event)]
+pub struct DamageTakenEvent {
+    pub source: Entity,
+    pub instigator: Entity,
+    pub weapon_type: Option<data::WeaponType>,
+    pub hit_zone: Option<data::HitZone>,
+    pub breakdown: DamageBreakdown,
+}
+
+#[derive(Debug, Event)]
+pub struct HealthChangedEvent {
+    pub previous: usize,
+    pub current: usize,
+    pub max: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct HealEvent {
+    pub amount: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct RechargeShieldEvent {
+    pub amount: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct DownedEvent {
+    pub instigator: Entity,
+    pub weapon_type: Option<data::WeaponType>,
+}
+
+#[derive(Debug, Event)]
+pub struct ReviveEvent {
+    pub reviver: Entity,
+}
+
+#[derive(Debug, Event)]
+pub struct DeathEvent {
+    pub instigator: Entity,
+    pub weapon_type: Option<data::WeaponType>,
+}
+
+#[derive(Debug)]
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (regen_shields, bleed_out))
+            .add_observer(on_damage)
+            .add_observer(on_revive)
+            .add_observer(on_heal)
+            .add_observer(on_recharge_shield)
+            .register_type::<HitBox>()
+            .register_type::<Health>()
+            .register_type::<Shield>()
+            .register_type::<Armor>();
+    }
+}
+
+fn regen_shields(time: Res<Time>, mut shield_query: Query<&mut Shield>) {
+    for mut shield in shield_query.iter_mut() {
+        if shield.current >= shield.capacity {
+            continue;
+        }
+
+        shield.regen_delay_timer.tick(time.delta());
+        if !shield.regen_delay_timer.finished() {
+            continue;
+        }
+
+        shield.regen_timer.tick(time.delta());
+        let regen = shield.regen_timer.times_finished_this_tick() as usize;
+        shield.current = (shield.current + regen).min(shield.capacity);
+    }
+}
+
+fn bleed_out(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut downed_query: Query<(Entity, &mut Downed)>,
+) {
+    for (entity, mut downed) in downed_query.iter_mut() {
+        downed.bleed_out_timer.tick(time.delta());
+        if downed.bleed_out_timer.just_finished() {
+            info!("{} bled out", entity);
+            kill(&mut commands, entity, downed.instigator, downed.weapon_type);
+        }
+    }
+}
+
+fn kill(
+    commands: &mut Commands,
+    entity: Entity,
+    instigator: Entity,
+    weapon_type: Option<data::WeaponType>,
+) {
+    info!("{} killed by {}", entity, instigator);
+
+    commands.entity(entity).remove::<Downed>();
+    commands.trigger_targets(
+        DeathEvent {
+            instigator,
+            weapon_type,
+        },
+        entity,
+    );
+}
+
+fn on_damage(
+    trigger: Trigger<DamageEvent>,
+    mut commands: Commands,
+    mut health_query: Query<(
+        &mut Health,
+        Option<&mut Shield>,
+        Option<&mut Armor>,
+        Option<&Downable>,
+        Has<Downed>,
+        Has<Invulnerable>,
+    )>,
+) {
+    let entity = trigger.entity();
+    let Ok((mut health, shield, armor, downable, downed, invulnerable)) =
+        health_query.get_mut(entity)
+    else {
+        return;
+    };
+
+    if invulnerable {
+        return;
+    }
+
+    if health.is_dead() {
+        // taking damage while down finishes you off
+        if downed {
+            kill(
+                &mut commands,
+                entity,
+                trigger.instigator,
+                trigger.weapon_type,
+            );
+        }
+        return;
+    }
+
+    let mut remaining = trigger.amount;
+    let mut breakdown = DamageBreakdown::default();
+
+    if let Some(mut shield) = shield {
+        let was_broken = shield.is_broken();
+        breakdown.shield = shield.absorb(&mut remaining);
+        breakdown.shield_broken = !was_broken && shield.is_broken();
+    }
+
+    if let Some(mut armor) = armor {
+        breakdown.armor = armor.absorb(&mut remaining);
+    }
+
+    let previous = health.current();
+    breakdown.health = health.damage(remaining);
+    info!(
+        "{} took {} damage from {} ({:?}, {:?}): {:?}",
+        entity,
+        breakdown.total(),
+        trigger.instigator,
+        trigger.weapon_type,
+        trigger.hit_zone,
+        breakdown
+    );
+
+    commands.trigger_targets(
+        DamageTakenEvent {
+            source: trigger.source,
+            instigator: trigger.instigator,
+            weapon_type: trigger.weapon_type,
+            hit_zone: trigger.hit_zone,
+            breakdown,
+        },
+        entity,
+    );
+
+    commands.trigger_targets(
+        HealthChangedEvent {
+            previous,
+            current: health.current(),
+            max: health.max(),
+        },
+        entity,
+    );
+
+    if health.is_dead() {
+        if let Some(downable) = downable {
+            info!("{} downed by {}", entity, trigger.instigator);
+
+            commands.entity(entity).insert(Downed {
+                bleed_out_timer: Timer::from_seconds(downable.bleed_out, TimerMode::Once),
+                instigator: trigger.instigator,
+                weapon_type: trigger.weapon_type,
+            });
+            commands.trigger_targets(
+                DownedEvent {
+                    instigator: trigger.instigator,
+                    weapon_type: trigger.weapon_type,
+                },
+                entity,
+            );
+        } else {
+            kill(
+                &mut commands,
+                entity,
+                trigger.instigator,
+                trigger.weapon_type,
+            );
+        }
+    }
+}
+
+fn on_revive(
+    trigger: Trigger<ReviveEvent>,
+    mut commands: Commands,
+    mut health_query: Query<(&mut Health, &Downable), With<Downed>>,
+) {
+    let entity = trigger.entity();
+    let Ok((mut health, downable)) = health_query.get_mut(entity) else {
+        return;
+    };
+
+    info!("{} revived by {}", entity, trigger.reviver);
+
+    let previous = health.current();
+    health.heal(downable.revive_health);
+
+    commands.entity(entity).remove::<Downed>();
+    commands.trigger_targets(
+        HealthChangedEvent {
+            previous,
+            current: health.current(),
+            max: health.max(),
+        },
+        entity,
+    );
+}
+
+fn on_heal(
+    trigger: Trigger<HealEvent>,
+    mut commands: Commands,
+    mut health_query: Query<&mut Health, Without<Downed>>,
+) {
+    let entity = trigger.entity();
+    let Ok(mut health) = health_query.get_mut(entity) else {
+        return;
+    };
+
+    // dead (or downed) players have to be revived
+    if health.is_dead() {
+        return;
+    }
+
+    let previous = health.current();
+    let healed = health.heal(trigger.amount);
+    info!("{} healed {}", entity, healed);
+
+    commands.trigger_targets(
+        HealthChangedEvent {
+            previous,
+            current: health.current(),
+            max: health.max(),
+        },
+        entity,
+    );
+}
+
+fn on_recharge_shield(trigger: Trigger<RechargeShieldEvent>, mut shield_query: Query<&mut Shield>) {
+    let entity = trigger.entity();
+    let Ok(mut shield) = shield_query.get_mut(entity) else {
+        return;
+    };
+
+    let recharged = shield.recharge(trigger.amount);
+    info!("{} recharged {} shield", entity, recharged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    #[derive(Debug, Default, Resource)]
+    struct DamageTaken(Vec<(Entity, DamageBreakdown)>);
+
+    fn create_world() -> World {
+        let mut world = test_utils::create_world();
+        world.init_resource::<DamageTaken>();
+        world.add_observer(on_damage);
+        world.add_observer(
+            |trigger: Trigger<DamageTakenEvent>, mut damage_taken: ResMut<DamageTaken>| {
+                damage_taken.0.push((trigger.entity(), trigger.breakdown));
+            },
+        );
+        world
+    }
+
+    fn create_armor(world: &World, armor_type: data::ArmorType) -> Armor {
+        let mut armor = Armor::default();
+        armor.equip(
+            armor_type,
+            world
+                .resource::<data::ArmorDataSource>()
+                .get(&armor_type)
+                .unwrap(),
+        );
+        armor
+    }
+
+    fn damage(world: &mut World, entity: Entity, amount: usize) {
+        world.trigger_targets(
+            DamageEvent {
+                source: Entity::PLACEHOLDER,
+                instigator: Entity::PLACEHOLDER,
+                weapon_type: None,
+                hit_zone: None,
+                amount,
+            },
+            entity,
+        );
+        world.flush();
+    }
+
+    #[test]
+    fn shield_absorbs_partial_damage() {
+        let mut world = create_world();
+
+        let entity = world
+            .spawn((Health::new(100), Shield::new(50, 1.0, 1.0)))
+            .id();
+        damage(&mut world, entity, 20);
+
+        assert_eq!(
+            world.resource::<DamageTaken>().0,
+            vec![(
+                entity,
+                DamageBreakdown {
+                    shield: 20,
+                    armor: 0,
+                    health: 0,
+                    shield_broken: false,
+                }
+            )]
+        );
+        assert_eq!(world.get::<Shield>(entity).unwrap().current(), 30);
+        assert_eq!(world.get::<Health>(entity).unwrap().current(), 100);
+    }
+
+    #[test]
+    fn overflows_shield_into_health() {
+        let mut world = create_world();
+
+        let entity = world
+            .spawn((Health::new(100), Shield::new(10, 1.0, 1.0)))
+            .id();
+        damage(&mut world, entity, 30);
+
+        assert_eq!(
+            world.resource::<DamageTaken>().0,
+            vec![(
+                entity,
+                DamageBreakdown {
+                    shield: 10,
+                    armor: 0,
+                    health: 20,
+                    shield_broken: true,
+                }
+            )]
+        );
+        assert!(world.get::<Shield>(entity).unwrap().is_broken());
+        assert_eq!(world.get::<Health>(entity).unwrap().current(), 80);
+    }
+
+    #[test]
+    fn splits_damage_between_shield_armor_and_health() {
+        let mut world = create_world();
+
+        // heavy armor soaks up 60% of whatever gets past the shield
+        let armor = create_armor(&world, data::ArmorType::Heavy);
+        let entity = world
+            .spawn((Health::new(100), Shield::new(10, 1.0, 1.0), armor))
+            .id();
+        damage(&mut world, entity, 60);
+
+        assert_eq!(
+            world.resource::<DamageTaken>().0,
+            vec![(
+                entity,
+                DamageBreakdown {
+                    shield: 10,
+                    armor: 30,
+                    health: 20,
+                    shield_broken: true,
+                }
+            )]
+        );
+        assert_eq!(world.get::<Armor>(entity).unwrap().current(), 70);
+        assert_eq!(world.get::<Health>(entity).unwrap().current(), 80);
+    }
+
+    #[test]
+    fn invulnerable_takes_no_damage() {
+        let mut world = create_world();
+
+        let entity = world
+            .spawn((Health::new(100), Shield::new(50, 1.0, 1.0), Invulnerable))
+            .id();
+        damage(&mut world, entity, 60);
+
+        assert!(world.resource::<DamageTaken>().0.is_empty());
+        assert_eq!(world.get::<Shield>(entity).unwrap().current(), 50);
+        assert_eq!(world.get::<Health>(entity).unwrap().current(), 100);
+    }
+}
@@ -1,10 +1,25 @@
 use bevy::prelude::*;
 
-use crate::{AppState, inventory, ui};
+use crate::{AppState, consumable, data, health, interactables, inventory, player, spawn, ui};
 
 #[derive(Debug, Component)]
 pub struct Hud;
 
+#[derive(Debug, Component)]
+struct HealthLabel;
+
+#[derive(Debug, Component)]
+struct ShieldLabel;
+
+#[derive(Debug, Component)]
+struct ArmorLabel;
+
+#[derive(Debug, Component)]
+struct InteractionLabel;
+
+#[derive(Debug, Component)]
+struct ChannelLabel;
+
 #[derive(Debug, Component)]
 struct PrimaryWeaponLabel;
 
@@ -14,12 +29,36 @@ struct SecondaryWeaponLabel;
 #[derive(Debug, Component)]
 struct InventoryLabel;
 
+const NOTICE_DURATION: f32 = 2.0;
+
+// clears itself when the timer runs out
+#[derive(Debug, Component)]
+struct NoticeLabel(Timer);
+
 #[derive(Debug)]
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_hud.run_if(in_state(AppState::InGame)));
+        app.add_systems(
+            Update,
+            (update_hud, update_notice).run_if(in_state(AppState::InGame)),
+        )
+        .add_observer(on_damage_taken)
+        .add_observer(on_health_added)
+        .add_observer(on_health_changed)
+        .add_observer(on_downed)
+        .add_observer(on_pickup_rejected)
+        .add_observer(on_loot_respawn_warning)
+        .add_observer(on_weapons_changed::<OnAdd, inventory::Inventory>)
+        .add_observer(on_weapons_changed::<inventory::WeaponEquippedEvent, ()>)
+        .add_observer(on_weapons_changed::<inventory::AmmoChangedEvent, ()>)
+        .add_observer(on_items_changed::<OnAdd, inventory::Inventory>)
+        .add_observer(on_items_changed::<inventory::ItemAddedEvent, ()>)
+        .add_observer(on_items_changed::<inventory::ItemRemovedEvent, ()>)
+        .add_observer(on_items_changed::<inventory::StackChangedEvent, ()>)
+        .add_observer(on_items_changed::<inventory::ItemsRearrangedEvent, ()>)
+        .add_observer(on_items_changed::<inventory::CapacityChangedEvent, ()>);
     }
 }
 
@@ -27,40 +66,245 @@ impl Plugin for HudPlugin {
 // especially since we're allocating every frame
 #[allow(clippy::type_complexity)]
 fn update_hud(
-    inventory: Res<inventory::Inventory>,
+    player_query: Query<
+        (
+            &health::Shield,
+            &health::Armor,
+            Option<&interactables::HoldInteraction>,
+            Option<&consumable::Channeling>,
+            Option<&player::CookingThrowable>,
+        ),
+        With<player::LocalPlayer>,
+    >,
     mut label_set: ParamSet<(
-        Query<&mut Text, With<PrimaryWeaponLabel>>,
-        Query<&mut Text, With<SecondaryWeaponLabel>>,
-        Query<&mut Text, With<InventoryLabel>>,
+        Query<&mut Text, With<ShieldLabel>>,
+        Query<&mut Text, With<ArmorLabel>>,
+        Query<&mut Text, With<InteractionLabel>>,
+        Query<&mut Text, With<ChannelLabel>>,
     )>,
 ) {
-    label_set.p0().single_mut().0 = format!(
-        "Primary Weapon: {:?}",
-        inventory
-            .get_primary_weapon()
-            .map(|weapon| (weapon.r#type, weapon.ammo_count))
-    );
+    let Ok((shield, armor, hold_interaction, channeling, cooking)) = player_query.get_single()
+    else {
+        return;
+    };
+
+    label_set.p0().single_mut().0 = if shield.is_broken() {
+        "Shield: BROKEN".to_owned()
+    } else {
+        format!("Shield: {}/{}", shield.current(), shield.capacity())
+    };
 
     label_set.p1().single_mut().0 = format!(
-        "Secondary Weapon: {:?}",
-        inventory
-            .get_secondary_weapon()
-            .map(|weapon| (weapon.r#type, weapon.ammo_count))
+        "Armor: {:?} {}/{}",
+        armor.get_type(),
+        armor.current(),
+        armor.capacity()
     );
 
-    let mut inventory_text = String::new();
+    label_set.p2().single_mut().0 = hold_interaction
+        .map(|hold_interaction| {
+            format!(
+                "{}: {:.0}%",
+                hold_interaction.target_type,
+                hold_interaction.get_progress() * 100.0
+            )
+        })
+        .unwrap_or_default();
+
+    label_set.p3().single_mut().0 = channeling
+        .map(|channeling| {
+            format!(
+                "{}: {:.0}%",
+                channeling.consumable_type,
+                channeling.get_progress() * 100.0
+            )
+        })
+        .or_else(|| {
+            cooking.map(|cooking| {
+                format!(
+                    "{}: {:.1}s",
+                    cooking.throwable_type,
+                    cooking.get_fuse_remaining()
+                )
+            })
+        })
+        .unwrap_or_default();
+}
+
+fn update_notice(time: Res<Time>, mut label_query: Query<(&mut Text, &mut NoticeLabel)>) {
+    for (mut label, mut notice) in label_query.iter_mut() {
+        notice.0.tick(time.delta());
+        if notice.0.just_finished() {
+            label.0.clear();
+        }
+    }
+}
+
+fn on_pickup_rejected(
+    trigger: Trigger<inventory::PickupRejectedEvent>,
+    player_query: Query<(), With<player::LocalPlayer>>,
+    mut label_query: Query<(&mut Text, &mut NoticeLabel)>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    let reason = match trigger.reason {
+        inventory::RejectReason::Full => "inventory full",
+        inventory::RejectReason::Overweight => "too heavy",
+        inventory::RejectReason::DoesNotFit => "doesn't fit",
+        inventory::RejectReason::WeaponSlotsFull => "weapon slots full",
+        inventory::RejectReason::NotStorable => "can't be stored",
+    };
+
+    if let Ok((mut label, mut notice)) = label_query.get_single_mut() {
+        label.0 = format!("Can't pick up {}: {}", trigger.item, reason);
+        notice.0.reset();
+    }
+}
+
+fn on_loot_respawn_warning(
+    trigger: Trigger<spawn::LootRespawnWarningEvent>,
+    mut label_query: Query<(&mut Text, &mut NoticeLabel)>,
+) {
+    // TODO: this should be a marker at the spawn instead
+    if let Ok((mut label, mut notice)) = label_query.get_single_mut() {
+        label.0 = format!("Loot respawning in {:.0}s", trigger.remaining);
+        notice.0.reset();
+    }
+}
+
+fn on_weapons_changed<E: Event, B: Bundle>(
+    trigger: Trigger<E, B>,
+    inventory: inventory::LocalInventory,
+    mut label_set: ParamSet<(
+        Query<&mut Text, With<PrimaryWeaponLabel>>,
+        Query<&mut Text, With<SecondaryWeaponLabel>>,
+    )>,
+) {
+    let Some(inventory) = inventory.get(trigger.entity()) else {
+        return;
+    };
+
+    if let Ok(mut label) = label_set.p0().get_single_mut() {
+        label.0 = format!(
+            "Primary Weapon: {:?}",
+            inventory
+                .get_primary_weapon()
+                .map(|weapon| (weapon.r#type, weapon.ammo_count))
+        );
+    }
+
+    if let Ok(mut label) = label_set.p1().get_single_mut() {
+        label.0 = format!(
+            "Secondary Weapon: {:?}",
+            inventory
+                .get_secondary_weapon()
+                .map(|weapon| (weapon.r#type, weapon.ammo_count))
+        );
+    }
+}
+
+fn on_items_changed<E: Event, B: Bundle>(
+    trigger: Trigger<E, B>,
+    item_data: inventory::ItemData,
+    inventory: inventory::LocalInventory,
+    mut label_query: Query<&mut Text, With<InventoryLabel>>,
+) {
+    let Some(inventory) = inventory.get(trigger.entity()) else {
+        return;
+    };
+
+    let mut inventory_text = match inventory.get_capacity() {
+        data::InventoryCapacity::Slots(slots) => {
+            format!("{}/{} slots\n", inventory.get_items().len(), slots)
+        }
+        data::InventoryCapacity::Weight(max_weight) => {
+            format!(
+                "{:.1}/{:.1} kg\n",
+                inventory.get_weight(&item_data),
+                max_weight
+            )
+        }
+        data::InventoryCapacity::Grid(size) => format!("{}x{} grid\n", size.x, size.y),
+    };
+    if let Some(backpack) = inventory.get_backpack() {
+        inventory_text.push_str(&format!("Backpack: {}\n", backpack));
+    }
     for item in inventory.get_items() {
         inventory_text.push_str(
             match item {
                 inventory::InventoryItem::Ammo(ammo_type, ammo_count) => {
                     format!("{}: {}\n", ammo_type, ammo_count)
                 }
+                inventory::InventoryItem::Consumable(consumable_type, count) => {
+                    format!("{}: {}\n", consumable_type, count)
+                }
                 _ => format!("{}\n", item),
             }
             .as_ref(),
         )
     }
-    label_set.p2().single_mut().0 = inventory_text;
+
+    if let Ok(mut label) = label_query.get_single_mut() {
+        label.0 = inventory_text;
+    }
+}
+
+fn on_damage_taken(
+    trigger: Trigger<health::DamageTakenEvent>,
+    player_query: Query<(), With<player::LocalPlayer>>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    if trigger.breakdown.shield_broken {
+        // TODO: flash the shield bar / play the shield break sound
+        info!("shield broken!");
+    }
+}
+
+fn on_health_added(
+    trigger: Trigger<OnAdd, health::Health>,
+    player_query: Query<&health::Health, With<player::LocalPlayer>>,
+    mut label_query: Query<&mut Text, With<HealthLabel>>,
+) {
+    let Ok(health) = player_query.get(trigger.entity()) else {
+        return;
+    };
+
+    if let Ok(mut label) = label_query.get_single_mut() {
+        label.0 = format!("Health: {}/{}", health.current(), health.max());
+    }
+}
+
+fn on_health_changed(
+    trigger: Trigger<health::HealthChangedEvent>,
+    player_query: Query<(), With<player::LocalPlayer>>,
+    mut label_query: Query<&mut Text, With<HealthLabel>>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    if let Ok(mut label) = label_query.get_single_mut() {
+        label.0 = format!("Health: {}/{}", trigger.current, trigger.max);
+    }
+}
+
+fn on_downed(
+    trigger: Trigger<health::DownedEvent>,
+    player_query: Query<(), With<player::LocalPlayer>>,
+    mut label_query: Query<&mut Text, With<HealthLabel>>,
+) {
+    if !player_query.contains(trigger.entity()) {
+        return;
+    }
+
+    if let Ok(mut label) = label_query.get_single_mut() {
+        label.0 = "Health: DOWNED".to_owned();
+    }
 }
 
 pub fn spawn_hud(commands: &mut Commands) {
@@ -68,6 +312,16 @@ pub fn spawn_hud(commands: &mut Commands) {
         .insert(Hud)
         .with_children(|parent| {
             ui::spawn_vbox_at(parent, (Val::Px(0.0), Val::Px(0.0))).with_children(|parent| {
+                ui::spawn_label(parent, "Health: -").insert(HealthLabel);
+                ui::spawn_label(parent, "Shield: -").insert(ShieldLabel);
+                ui::spawn_label(parent, "Armor: -").insert(ArmorLabel);
+                ui::spawn_label(parent, "").insert(InteractionLabel);
+                ui::spawn_label(parent, "").insert(ChannelLabel);
+                ui::spawn_label(parent, "").insert(NoticeLabel(Timer::from_seconds(
+                    NOTICE_DURATION,
+                    TimerMode::Once,
+                )));
+
                 ui::spawn_label(parent, "Primary Weapon: None").insert(PrimaryWeaponLabel);
                 ui::spawn_label(parent, "Secondary Weapon: None").insert(SecondaryWeaponLabel);
 
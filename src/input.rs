@@ -16,8 +16,13 @@ pub struct InputState {
     pub primary: Vec2,
     pub secondary: Vec2,
 
+    pub interacting: bool,
+
     firing_pressed: bool,
     pub firing: bool,
+
+    throwing_pressed: bool,
+    pub throwing: bool,
 }
 
 // TODO: genericize input events
@@ -28,15 +33,53 @@ pub struct InteractInputEvent;
 #[derive(Debug, Default, Event)]
 pub struct ToggleWeaponInputEvent;
 
+#[derive(Debug, Default, Event)]
+pub struct QuickHealInputEvent;
+
 #[derive(Debug, Default, Event)]
 pub struct FiringInputStartEvent;
 
 #[derive(Debug, Default, Event)]
 pub struct FiringInputEndEvent;
 
+#[derive(Debug, Default, Event)]
+pub struct ThrowingInputStartEvent;
+
+#[derive(Debug, Default, Event)]
+pub struct ThrowingInputEndEvent;
+
 #[derive(Debug, Deref, Event)]
 pub struct SelectWeaponInputEvent(inventory::WeaponSlot);
 
+// inventory slot to use
+#[derive(Debug, Deref, Event)]
+pub struct UseItemInputEvent(usize);
+
+#[derive(Debug, Default, Event)]
+pub struct ToggleSortModeInputEvent;
+
+// inventory slot to drop
+#[derive(Debug, Deref, Event)]
+pub struct DropItemInputEvent(usize);
+
+#[derive(Debug, Default, Event)]
+pub struct DropWeaponInputEvent;
+
+// inventory slot to move up one slot
+#[derive(Debug, Deref, Event)]
+pub struct MoveItemInputEvent(usize);
+
+// inventory slot to split in half
+#[derive(Debug, Deref, Event)]
+pub struct SplitStackInputEvent(usize);
+
+// menu navigation, +y is down
+#[derive(Debug, Deref, Event)]
+pub struct NavigateInputEvent(IVec2);
+
+#[derive(Debug, Default, Event)]
+pub struct ConfirmInputEvent;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
 pub struct InputSet;
 
@@ -49,7 +92,7 @@ impl Plugin for InputPlugin {
             Update,
             (
                 ((handle_gamepad_events, update_gamepad).chain(), update_mnk),
-                update_firing,
+                (update_firing, update_throwing),
             )
                 .chain()
                 .in_set(InputSet),
@@ -60,8 +103,19 @@ impl Plugin for InputPlugin {
         .add_event::<InteractInputEvent>()
         .add_event::<ToggleWeaponInputEvent>()
         .add_event::<SelectWeaponInputEvent>()
+        .add_event::<QuickHealInputEvent>()
+        .add_event::<UseItemInputEvent>()
+        .add_event::<DropItemInputEvent>()
+        .add_event::<DropWeaponInputEvent>()
+        .add_event::<ToggleSortModeInputEvent>()
+        .add_event::<MoveItemInputEvent>()
+        .add_event::<SplitStackInputEvent>()
+        .add_event::<NavigateInputEvent>()
+        .add_event::<ConfirmInputEvent>()
         .add_event::<FiringInputStartEvent>()
-        .add_event::<FiringInputEndEvent>();
+        .add_event::<FiringInputEndEvent>()
+        .add_event::<ThrowingInputStartEvent>()
+        .add_event::<ThrowingInputEndEvent>();
     }
 }
 
@@ -69,7 +123,10 @@ fn clear_input(mut input_state: ResMut<InputState>) {
     input_state.primary = Vec2::ZERO;
     input_state.secondary = Vec2::ZERO;
 
+    input_state.interacting = false;
+
     input_state.firing_pressed = false;
+    input_state.throwing_pressed = false;
 }
 
 fn handle_gamepad_events(
@@ -100,6 +157,7 @@ fn handle_gamepad_events(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_mnk(
     keys: Res<ButtonInput<KeyCode>>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
@@ -108,6 +166,15 @@ fn update_mnk(
     mut evr_motion: EventReader<MouseMotion>,
     mut evw_interact: EventWriter<InteractInputEvent>,
     mut evw_select_weapons: EventWriter<SelectWeaponInputEvent>,
+    mut evw_quick_heal: EventWriter<QuickHealInputEvent>,
+    mut evw_use_item: EventWriter<UseItemInputEvent>,
+    mut evw_drop_item: EventWriter<DropItemInputEvent>,
+    mut evw_drop_weapon: EventWriter<DropWeaponInputEvent>,
+    mut evw_toggle_sort_mode: EventWriter<ToggleSortModeInputEvent>,
+    mut evw_move_item: EventWriter<MoveItemInputEvent>,
+    mut evw_split_stack: EventWriter<SplitStackInputEvent>,
+    mut evw_navigate: EventWriter<NavigateInputEvent>,
+    mut evw_confirm: EventWriter<ConfirmInputEvent>,
 ) {
     /*if !settings.mnk.enabled {
         return;
@@ -142,6 +209,7 @@ fn update_mnk(
     if keys.just_pressed(KeyCode::KeyE) {
         evw_interact.send_default();
     }
+    input_state.interacting |= keys.pressed(KeyCode::KeyE);
 
     if keys.just_pressed(KeyCode::Digit1) {
         evw_select_weapons.send(SelectWeaponInputEvent(inventory::WeaponSlot::Primary));
@@ -149,16 +217,84 @@ fn update_mnk(
     if keys.just_pressed(KeyCode::Digit2) {
         evw_select_weapons.send(SelectWeaponInputEvent(inventory::WeaponSlot::Secondary));
     }
+    if keys.just_pressed(KeyCode::Digit3) {
+        evw_select_weapons.send(SelectWeaponInputEvent(inventory::WeaponSlot::Throwable));
+    }
+
+    if keys.just_pressed(KeyCode::KeyH) {
+        evw_quick_heal.send_default();
+    }
+
+    if keys.just_pressed(KeyCode::KeyX) {
+        evw_drop_weapon.send_default();
+    }
+
+    if keys.just_pressed(KeyCode::KeyT) {
+        evw_toggle_sort_mode.send_default();
+    }
+
+    // 4-9 use the first inventory slots
+    // holding shift drops the stack, alt moves the item up a slot and ctrl splits the stack
+    // TODO: gamepad needs an inventory menu for this
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    for (slot, key) in [
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if !keys.just_pressed(key) {
+            continue;
+        }
+
+        if shift {
+            evw_drop_item.send(DropItemInputEvent(slot));
+        } else if alt {
+            evw_move_item.send(MoveItemInputEvent(slot));
+        } else if ctrl {
+            evw_split_stack.send(SplitStackInputEvent(slot));
+        } else {
+            evw_use_item.send(UseItemInputEvent(slot));
+        }
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        evw_navigate.send(NavigateInputEvent(IVec2::NEG_Y));
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        evw_navigate.send(NavigateInputEvent(IVec2::Y));
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        evw_navigate.send(NavigateInputEvent(IVec2::NEG_X));
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        evw_navigate.send(NavigateInputEvent(IVec2::X));
+    }
+    if keys.just_pressed(KeyCode::Enter) {
+        evw_confirm.send_default();
+    }
 
     input_state.firing_pressed |= mouse_buttons.pressed(MouseButton::Left);
+    input_state.throwing_pressed |= keys.pressed(KeyCode::KeyG);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_gamepad(
     //settings: Res<Settings>,
     gamepad: Option<Res<ConnectedGamepad>>,
     mut input_state: ResMut<InputState>,
     mut evw_interact: EventWriter<InteractInputEvent>,
     mut evw_toggle_weapons: EventWriter<ToggleWeaponInputEvent>,
+    mut evw_quick_heal: EventWriter<QuickHealInputEvent>,
+    mut evw_navigate: EventWriter<NavigateInputEvent>,
+    mut evw_confirm: EventWriter<ConfirmInputEvent>,
     gamepads: Query<&Gamepad>,
 ) {
     /*if !settings.gamepad.enabled {
@@ -192,11 +328,30 @@ fn update_gamepad(
     if gamepad.just_pressed(GamepadButton::West) {
         evw_interact.send_default();
     }
+    input_state.interacting |= gamepad.pressed(GamepadButton::West);
     if gamepad.just_pressed(GamepadButton::North) {
         evw_toggle_weapons.send_default();
     }
+    // dpad up is also quick heal outside of menus
+    if gamepad.just_pressed(GamepadButton::DPadUp) {
+        evw_quick_heal.send_default();
+        evw_navigate.send(NavigateInputEvent(IVec2::NEG_Y));
+    }
+    if gamepad.just_pressed(GamepadButton::DPadDown) {
+        evw_navigate.send(NavigateInputEvent(IVec2::Y));
+    }
+    if gamepad.just_pressed(GamepadButton::DPadLeft) {
+        evw_navigate.send(NavigateInputEvent(IVec2::NEG_X));
+    }
+    if gamepad.just_pressed(GamepadButton::DPadRight) {
+        evw_navigate.send(NavigateInputEvent(IVec2::X));
+    }
+    if gamepad.just_pressed(GamepadButton::South) {
+        evw_confirm.send_default();
+    }
 
     input_state.firing_pressed |= gamepad.pressed(GamepadButton::RightTrigger);
+    input_state.throwing_pressed |= gamepad.pressed(GamepadButton::LeftTrigger);
 }
 
 fn update_firing(
@@ -212,3 +367,17 @@ fn update_firing(
 
     input_state.firing = input_state.firing_pressed;
 }
+
+fn update_throwing(
+    mut input_state: ResMut<InputState>,
+    mut evw_throwing_start: EventWriter<ThrowingInputStartEvent>,
+    mut evw_throwing_end: EventWriter<ThrowingInputEndEvent>,
+) {
+    if !input_state.throwing && input_state.throwing_pressed {
+        evw_throwing_start.send_default();
+    } else if input_state.throwing && !input_state.throwing_pressed {
+        evw_throwing_end.send_default();
+    }
+
+    input_state.throwing = input_state.throwing_pressed;
+}
@@ -1,19 +1,111 @@
 use std::ops::Deref;
 
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 
-use crate::{GameCollisionLayers, INTERACTABLE_INTERACT_LAYERS, inventory, loot};
+use crate::{
+    GameCollisionLayers, INTERACTABLE_INTERACT_LAYERS, assets, container, data, health, inventory,
+    loot,
+};
 
 const INTERACTABLE_RADIUS: f32 = 1.0;
+const REVIVE_DURATION: f32 = 3.0;
+const SEARCH_DURATION: f32 = 1.5;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Component, strum::Display)]
 pub enum InteractableType {
     GroundLoot,
+    Revive,
+    Container,
+}
+
+impl InteractableType {
+    // interactables with a hold duration must be held to interact with
+    pub fn get_hold_duration(&self) -> Option<f32> {
+        match self {
+            Self::GroundLoot => None,
+            Self::Revive => Some(REVIVE_DURATION),
+            Self::Container => Some(SEARCH_DURATION),
+        }
+    }
+}
+
+// everything needed to pick up an item
+#[derive(SystemParam)]
+pub struct PickupData<'w> {
+    game_assets: Res<'w, assets::GameAssets>,
+    armor_datum: Res<'w, data::ArmorDataSource>,
+    backpack_datum: Res<'w, data::BackpackDataSource>,
+    item_data: inventory::ItemData<'w>,
+}
+
+// equips armor and backpacks, everything else goes in the inventory
+pub fn pickup_item(
+    commands: &mut Commands,
+    pickup_data: &PickupData,
+    item: inventory::InventoryItem,
+    inventory: &mut inventory::Inventory,
+    armor: &mut health::Armor,
+    global_transform: &GlobalTransform,
+) -> Result<(), inventory::AddItemRejection> {
+    match item {
+        inventory::InventoryItem::Armor(armor_type) => {
+            armor.equip(
+                armor_type,
+                pickup_data.armor_datum.get(&armor_type).unwrap(),
+            );
+            Ok(())
+        }
+        inventory::InventoryItem::Backpack(backpack_type) => {
+            let (previous, spilled) = inventory.equip_backpack(
+                backpack_type,
+                pickup_data.backpack_datum.get(&backpack_type).unwrap(),
+                &pickup_data.item_data,
+            );
+
+            for item in previous
+                .map(inventory::InventoryItem::Backpack)
+                .into_iter()
+                .chain(spilled)
+            {
+                loot::drop_ground_loot(commands, &pickup_data.game_assets, item, global_transform);
+            }
+            Ok(())
+        }
+        item => inventory.add_item(item, &pickup_data.item_data),
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct HoldInteraction {
+    pub target: Entity,
+    pub target_type: InteractableType,
+    timer: Timer,
+}
+
+impl HoldInteraction {
+    pub fn new(target: Entity, target_type: InteractableType, duration: f32) -> Self {
+        Self {
+            target,
+            target_type,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+
+    pub fn get_progress(&self) -> f32 {
+        self.timer.fraction()
+    }
+
+    // returns true when the interaction completes
+    pub fn tick(&mut self, time: &Time) -> bool {
+        self.timer.tick(time.delta());
+        self.timer.just_finished()
+    }
 }
 
 #[derive(Debug, Event)]
 pub struct InteractEvent {
+    pub player: Entity,
     pub target: Entity,
     pub target_type: InteractableType,
 }
@@ -23,28 +115,108 @@ pub struct InteractablesPlugin;
 
 impl Plugin for InteractablesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_observer(on_interact);
+        app.add_observer(on_interact)
+            .add_observer(on_downed)
+            .add_observer(on_revive);
     }
 }
 
 fn on_interact(
     trigger: Trigger<InteractEvent>,
     mut commands: Commands,
-    mut inventory: ResMut<inventory::Inventory>,
-    ground_loot_query: Query<&loot::GroundLoot>,
+    pickup_data: PickupData,
+    mut ground_loot_query: Query<&mut loot::GroundLoot>,
+    mut player_query: Query<(
+        &mut inventory::Inventory,
+        &mut health::Armor,
+        &GlobalTransform,
+    )>,
 ) {
     match trigger.target_type {
         InteractableType::GroundLoot => {
-            let loot = ground_loot_query.get(trigger.target).unwrap();
+            let mut loot = ground_loot_query.get_mut(trigger.target).unwrap();
+            let Ok((mut inventory, mut armor, global_transform)) =
+                player_query.get_mut(trigger.player)
+            else {
+                return;
+            };
 
-            if inventory.add_item(*loot.deref()) {
+            let result = pickup_item(
+                &mut commands,
+                &pickup_data,
+                *loot.deref(),
+                &mut inventory,
+                &mut armor,
+                global_transform,
+            );
+
+            let Err(rejection) = result else {
                 info!("picked up ground loot {:?}", loot.deref());
                 commands.entity(trigger.target).despawn_recursive();
+                return;
+            };
+
+            // partial pickups leave the remainder on the ground
+            if rejection.remaining != *loot.deref() {
+                info!(
+                    "picked up part of ground loot {:?}, leaving {:?}",
+                    loot.deref(),
+                    rejection.remaining
+                );
+                **loot = rejection.remaining;
+            }
+
+            commands.trigger_targets(
+                inventory::PickupRejectedEvent {
+                    item: rejection.remaining,
+                    reason: rejection.reason,
+                },
+                trigger.player,
+            );
+        }
+        InteractableType::Container => {
+            info!("open container {}", trigger.target);
+            commands
+                .entity(trigger.player)
+                .insert(container::Searching::new(trigger.target));
+        }
+        InteractableType::Revive => {
+            // TODO: only teammates should be able to revive
+            if trigger.player != trigger.target {
+                commands.trigger_targets(
+                    health::ReviveEvent {
+                        reviver: trigger.player,
+                    },
+                    trigger.target,
+                );
             }
         }
     }
 }
 
+fn on_downed(trigger: Trigger<health::DownedEvent>, mut commands: Commands) {
+    commands.entity(trigger.entity()).with_children(|parent| {
+        spawn_interactable(parent, InteractableType::Revive).insert(ColliderDensity(0.0));
+    });
+}
+
+fn on_revive(
+    trigger: Trigger<health::ReviveEvent>,
+    mut commands: Commands,
+    children_query: Query<&Children>,
+    interactable_query: Query<&InteractableType>,
+) {
+    let Ok(children) = children_query.get(trigger.entity()) else {
+        return;
+    };
+
+    for child in children.iter() {
+        if matches!(interactable_query.get(*child), Ok(InteractableType::Revive)) {
+            commands.entity(*child).despawn_recursive();
+        }
+    }
+}
+
 pub fn spawn_interactable<'a>(
     parent: &'a mut ChildBuilder,
     r#type: InteractableType,
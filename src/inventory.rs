@@ -1,25 +1,43 @@
-use bevy::prelude::*;
+use std::cmp::Reverse;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
 use rand::prelude::*;
 use strum::{EnumCount, IntoEnumIterator};
 
-use crate::{RandomSource, data, weapon};
+use crate::{RandomSource, RandomStream, assets, consumable, data, loot, player, weapon};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display, strum::EnumCount)]
 pub enum InventoryItem {
     Weapon(data::WeaponType, usize),
     Ammo(data::AmmoType, usize),
-    Throwable,
-    Consumable,
+    Throwable(data::ThrowableType),
+    Consumable(data::ConsumableType, usize),
+    // armor and backpacks are equipped on pickup rather than stored
+    Armor(data::ArmorType),
+    Backpack(data::BackpackType),
     // TODO: character mods (abilities, passives, etc)
     // TODO: weapon mods
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect, strum::Display)]
+pub enum ItemCategory {
+    Weapon,
+    Ammo,
+    Throwable,
+    Consumable,
+    Armor,
+    Backpack,
+}
+
 impl InventoryItem {
     pub fn random_loot(
         rng: &mut RandomSource,
         weapon_datum: &data::WeaponDatum,
         ammo_datum: &data::AmmoDatum,
+        consumable_datum: &data::ConsumableDatum,
     ) -> Self {
+        let rng = rng.stream(RandomStream::Loot);
+
         // TODO: bro this sucks lol
         match rng.random_range(..Self::COUNT) {
             0 => {
@@ -32,11 +50,115 @@ impl InventoryItem {
                 let ammo_data = ammo_datum.get(&ammo_type).unwrap();
                 Self::Ammo(ammo_type, ammo_data.loot_size)
             }
-            2 => Self::Throwable,
-            3 => Self::Consumable,
+            2 => Self::Throwable(data::ThrowableType::iter().choose(rng).unwrap()),
+            3 => {
+                let consumable_type = data::ConsumableType::iter().choose(rng).unwrap();
+                let consumable_data = consumable_datum.get(&consumable_type).unwrap();
+                Self::Consumable(consumable_type, consumable_data.loot_size)
+            }
+            4 => Self::Armor(data::ArmorType::iter().choose(rng).unwrap()),
+            5 => Self::Backpack(data::BackpackType::iter().choose(rng).unwrap()),
             _ => unreachable!(),
         }
     }
+
+    pub fn get_category(&self) -> ItemCategory {
+        match self {
+            Self::Weapon(_, _) => ItemCategory::Weapon,
+            Self::Ammo(_, _) => ItemCategory::Ammo,
+            Self::Throwable(_) => ItemCategory::Throwable,
+            Self::Consumable(_, _) => ItemCategory::Consumable,
+            Self::Armor(_) => ItemCategory::Armor,
+            Self::Backpack(_) => ItemCategory::Backpack,
+        }
+    }
+
+    // stackable items return their stack size
+    pub fn get_stack_size(&self, item_data: &ItemData) -> Option<usize> {
+        match self {
+            Self::Ammo(ammo_type, _) => Some(item_data.ammo.get(ammo_type).unwrap().stack_size),
+            Self::Consumable(consumable_type, _) => Some(
+                item_data
+                    .consumable
+                    .get(consumable_type)
+                    .unwrap()
+                    .stack_size,
+            ),
+            Self::Weapon(_, _) | Self::Throwable(_) | Self::Armor(_) | Self::Backpack(_) => None,
+        }
+    }
+
+    // the weight of the whole stack
+    pub fn get_weight(&self, item_data: &ItemData) -> f32 {
+        match self {
+            Self::Ammo(ammo_type, count) => {
+                item_data.ammo.get(ammo_type).unwrap().weight * *count as f32
+            }
+            Self::Consumable(consumable_type, count) => {
+                item_data.consumable.get(consumable_type).unwrap().weight * *count as f32
+            }
+            Self::Throwable(throwable_type) => {
+                item_data.throwable.get(throwable_type).unwrap().weight
+            }
+            // weapons and equipment don't take up inventory space
+            Self::Weapon(_, _) | Self::Armor(_) | Self::Backpack(_) => 0.0,
+        }
+    }
+
+    pub fn get_footprint(&self, item_data: &ItemData) -> UVec2 {
+        match self {
+            Self::Ammo(ammo_type, _) => item_data.ammo.get(ammo_type).unwrap().footprint,
+            Self::Consumable(consumable_type, _) => {
+                item_data.consumable.get(consumable_type).unwrap().footprint
+            }
+            Self::Throwable(throwable_type) => {
+                item_data.throwable.get(throwable_type).unwrap().footprint
+            }
+            Self::Weapon(_, _) | Self::Armor(_) | Self::Backpack(_) => UVec2::ZERO,
+        }
+    }
+
+    pub fn get_count(&self) -> usize {
+        match self {
+            Self::Ammo(_, count) | Self::Consumable(_, count) => *count,
+            Self::Weapon(_, _) | Self::Throwable(_) | Self::Armor(_) | Self::Backpack(_) => 1,
+        }
+    }
+
+    pub fn is_stackable(&self) -> bool {
+        matches!(self, Self::Ammo(_, _) | Self::Consumable(_, _))
+    }
+
+    // only meaningful for stackable items
+    pub fn with_count(self, count: usize) -> Self {
+        match self {
+            Self::Ammo(ammo_type, _) => Self::Ammo(ammo_type, count),
+            Self::Consumable(consumable_type, _) => Self::Consumable(consumable_type, count),
+            Self::Weapon(_, _) | Self::Throwable(_) | Self::Armor(_) | Self::Backpack(_) => self,
+        }
+    }
+
+    // category, then type, then largest stacks first
+    fn sort_key(&self) -> (usize, usize, Reverse<usize>) {
+        let category = self.get_category() as usize;
+        let count = Reverse(self.get_count());
+        match self {
+            Self::Weapon(weapon_type, _) => (category, *weapon_type as usize, count),
+            Self::Ammo(ammo_type, _) => (category, *ammo_type as usize, count),
+            Self::Throwable(throwable_type) => (category, *throwable_type as usize, count),
+            Self::Consumable(consumable_type, _) => (category, *consumable_type as usize, count),
+            Self::Armor(armor_type) => (category, *armor_type as usize, count),
+            Self::Backpack(backpack_type) => (category, *backpack_type as usize, count),
+        }
+    }
+
+    pub fn stacks_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Ammo(a, _), Self::Ammo(b, _)) => a == b,
+            (Self::Consumable(a, _), Self::Consumable(b, _)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Reflect, strum::Display)]
@@ -44,32 +166,104 @@ pub enum WeaponSlot {
     #[default]
     Primary,
     Secondary,
+    // holds whatever throwable is next in the inventory
+    Throwable,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Reflect, strum::Display)]
+pub enum SortMode {
+    // items are kept sorted as they're added
+    #[default]
+    Automatic,
+    // items stay wherever the player puts them
+    Manual,
+}
+
+// changes are buffered on the inventory and emitted as events once a frame
+#[derive(Debug, Copy, Clone)]
+enum InventoryChange {
+    ItemAdded(InventoryItem),
+    ItemRemoved(InventoryItem),
+    StackChanged {
+        item: InventoryItem,
+        previous_count: usize,
+    },
+    ItemsRearranged,
+    WeaponEquipped(WeaponSlot, Option<data::WeaponType>),
+    WeaponSlotChanged {
+        previous: WeaponSlot,
+        current: WeaponSlot,
+    },
+    AmmoChanged {
+        weapon_slot: WeaponSlot,
+        previous: usize,
+        current: usize,
+    },
+    CapacityChanged,
+}
+
+// the item data needed to stack and fit items in an inventory
+#[derive(SystemParam)]
+pub struct ItemData<'w> {
+    ammo: Res<'w, data::AmmoDataSource>,
+    consumable: Res<'w, data::ConsumableDataSource>,
+    throwable: Res<'w, data::ThrowableDataSource>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum::Display)]
+pub enum RejectReason {
+    // out of slots
+    Full,
+    Overweight,
+    // no room left in the grid
+    DoesNotFit,
+    WeaponSlotsFull,
+    // the item can't go in the inventory at all
+    NotStorable,
 }
 
-#[derive(Debug, Resource, Reflect)]
+#[derive(Debug, Copy, Clone)]
+pub struct AddItemRejection {
+    pub reason: RejectReason,
+    // whatever didn't fit in the inventory
+    pub remaining: InventoryItem,
+}
+
+// floating point slop when checking weight limits
+const WEIGHT_EPSILON: f32 = 0.001;
+
+#[derive(Debug, Component, Reflect)]
 pub struct Inventory {
     primary: Option<weapon::Weapon>,
     secondary: Option<weapon::Weapon>,
     selected_weapon: WeaponSlot,
 
-    size: usize,
+    // capacity without a backpack
+    base_capacity: data::InventoryCapacity,
+    capacity: data::InventoryCapacity,
+    backpack: Option<data::BackpackType>,
     items: Vec<InventoryItem>,
+    sort_mode: SortMode,
+
+    #[reflect(ignore)]
+    changes: Vec<InventoryChange>,
 }
 
-impl Default for Inventory {
-    fn default() -> Self {
+impl Inventory {
+    pub fn new(capacity: data::InventoryCapacity) -> Self {
         Self {
             primary: None,
             secondary: None,
             selected_weapon: WeaponSlot::default(),
-            // TODO: size should come from data
-            size: 10,
-            items: Vec::with_capacity(10),
+            base_capacity: capacity,
+            capacity,
+            backpack: None,
+            items: Vec::new(),
+            sort_mode: SortMode::default(),
+            changes: Vec::new(),
         }
     }
-}
 
-impl Inventory {
     pub fn has_weapon(&self) -> bool {
         self.primary.is_some() || self.secondary.is_some()
     }
@@ -82,14 +276,187 @@ impl Inventory {
         self.secondary.as_ref()
     }
 
+    // checks if either weapon fires the ammo type
+    pub fn uses_ammo(&self, ammo_type: data::AmmoType, weapon_datum: &data::WeaponDatum) -> bool {
+        self.primary
+            .iter()
+            .chain(self.secondary.iter())
+            .any(|weapon| weapon_datum.get(&weapon.r#type).unwrap().ammo_type == ammo_type)
+    }
+
     pub fn get_items(&self) -> &Vec<InventoryItem> {
         &self.items
     }
 
+    pub fn get_capacity(&self) -> data::InventoryCapacity {
+        self.capacity
+    }
+
+    pub fn get_backpack(&self) -> Option<data::BackpackType> {
+        self.backpack
+    }
+
+    // swaps in the new backpack, spilling whatever no longer fits
+    // returns the previous backpack and the spilled items
+    pub fn equip_backpack(
+        &mut self,
+        backpack_type: data::BackpackType,
+        backpack_data: &data::BackpackData,
+        item_data: &ItemData,
+    ) -> (Option<data::BackpackType>, Vec<InventoryItem>) {
+        info!("equip backpack {}", backpack_type);
+
+        let previous = self.backpack.replace(backpack_type);
+        self.capacity = self.base_capacity.with_backpack(backpack_data);
+        self.changes.push(InventoryChange::CapacityChanged);
+
+        // spill from the back so the most important items are kept
+        let mut spilled = vec![];
+        while !self.items.is_empty() && !self.is_within_capacity(item_data) {
+            let item = self.remove_item(self.items.len() - 1);
+            spilled.push(item);
+        }
+
+        if !spilled.is_empty() {
+            info!("backpack overflow spilled {:?}", spilled);
+        }
+
+        (previous, spilled)
+    }
+
+    fn is_within_capacity(&self, item_data: &ItemData) -> bool {
+        match self.capacity {
+            data::InventoryCapacity::Slots(slots) => self.items.len() <= slots,
+            data::InventoryCapacity::Weight(max_weight) => {
+                self.get_weight(item_data) <= max_weight + WEIGHT_EPSILON
+            }
+            data::InventoryCapacity::Grid(size) => {
+                let footprints = self
+                    .items
+                    .iter()
+                    .map(|item| item.get_footprint(item_data))
+                    .collect::<Vec<_>>();
+                pack_grid(size, &footprints).is_some()
+            }
+        }
+    }
+
+    pub fn get_weight(&self, item_data: &ItemData) -> f32 {
+        self.items
+            .iter()
+            .map(|item| item.get_weight(item_data))
+            .sum()
+    }
+
+    // checks if a new slot holding the item would fit
+    fn check_fit(&self, item: &InventoryItem, item_data: &ItemData) -> Result<(), RejectReason> {
+        match self.capacity {
+            data::InventoryCapacity::Slots(slots) => {
+                if self.items.len() >= slots {
+                    return Err(RejectReason::Full);
+                }
+            }
+            data::InventoryCapacity::Weight(max_weight) => {
+                if self.get_weight(item_data) + item.get_weight(item_data)
+                    > max_weight + WEIGHT_EPSILON
+                {
+                    return Err(RejectReason::Overweight);
+                }
+            }
+            data::InventoryCapacity::Grid(size) => {
+                let footprints = self
+                    .items
+                    .iter()
+                    .chain(std::iter::once(item))
+                    .map(|item| item.get_footprint(item_data))
+                    .collect::<Vec<_>>();
+                if pack_grid(size, &footprints).is_none() {
+                    return Err(RejectReason::DoesNotFit);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_sort_mode(&self) -> SortMode {
+        self.sort_mode
+    }
+
+    pub fn set_sort_mode(&mut self, sort_mode: SortMode) {
+        info!("set inventory sort mode {}", sort_mode);
+
+        self.sort_mode = sort_mode;
+        self.sort_items();
+    }
+
+    fn sort_items(&mut self) {
+        if self.sort_mode != SortMode::Automatic
+            || self.items.is_sorted_by_key(InventoryItem::sort_key)
+        {
+            return;
+        }
+
+        self.items.sort_by_key(InventoryItem::sort_key);
+        self.changes.push(InventoryChange::ItemsRearranged);
+    }
+
+    // returns false if the slots couldn't be swapped
+    pub fn swap_slots(&mut self, a: usize, b: usize) -> bool {
+        if self.sort_mode != SortMode::Manual {
+            warn!("inventory slots can only be swapped in manual sort mode");
+            return false;
+        }
+
+        if a >= self.items.len() || b >= self.items.len() {
+            warn!("invalid inventory slot swap {} <-> {}", a, b);
+            return false;
+        }
+
+        self.items.swap(a, b);
+        self.changes.push(InventoryChange::ItemsRearranged);
+
+        true
+    }
+
+    // moves count items from the stack into a new slot
+    // returns false if the stack couldn't be split
+    pub fn split_stack(&mut self, slot: usize, count: usize, item_data: &ItemData) -> bool {
+        let Some(item) = self.items.get(slot).copied() else {
+            warn!("invalid inventory slot {}", slot);
+            return false;
+        };
+
+        let current = item.get_count();
+        if !item.is_stackable() || count == 0 || count >= current {
+            warn!("can't split {} from inventory stack {:?}", count, item);
+            return false;
+        }
+
+        match self.check_fit(&item.with_count(count), item_data) {
+            // splitting doesn't change the total weight
+            Ok(()) | Err(RejectReason::Overweight) => (),
+            Err(reason) => {
+                warn!("no room to split inventory stack: {}", reason);
+                return false;
+            }
+        }
+
+        self.set_stack_count(slot, current - count);
+        self.items.insert(slot + 1, item.with_count(count));
+        self.changes
+            .push(InventoryChange::ItemAdded(item.with_count(count)));
+
+        self.sort_items();
+
+        true
+    }
+
     fn get_weapon_item(&self, weapon_slot: WeaponSlot) -> Option<&weapon::Weapon> {
         match weapon_slot {
             WeaponSlot::Primary => self.primary.as_ref(),
             WeaponSlot::Secondary => self.secondary.as_ref(),
+            WeaponSlot::Throwable => None,
         }
     }
 
@@ -97,25 +464,59 @@ impl Inventory {
         match weapon_slot {
             WeaponSlot::Primary => self.primary.as_mut(),
             WeaponSlot::Secondary => self.secondary.as_mut(),
+            WeaponSlot::Throwable => None,
         }
     }
 
+    pub fn get_selected_weapon(&self) -> WeaponSlot {
+        self.selected_weapon
+    }
+
     pub fn get_selected_weapon_item(&mut self) -> Option<&weapon::Weapon> {
         self.get_weapon_item(self.selected_weapon)
     }
 
-    fn get_unselected_weapon_item(&mut self) -> Option<&weapon::Weapon> {
+    // returns the (selected, unselected) weapon slots
+    // the primary slot stands in for the throwable slot
+    fn get_weapon_slots(&self) -> (WeaponSlot, WeaponSlot) {
         match self.selected_weapon {
-            WeaponSlot::Primary => self.get_weapon_item(WeaponSlot::Secondary),
-            WeaponSlot::Secondary => self.get_weapon_item(WeaponSlot::Primary),
+            WeaponSlot::Secondary => (WeaponSlot::Secondary, WeaponSlot::Primary),
+            WeaponSlot::Primary | WeaponSlot::Throwable => {
+                (WeaponSlot::Primary, WeaponSlot::Secondary)
+            }
         }
     }
 
-    pub fn get_selected_weapon_item_mut(&mut self) -> Option<&mut weapon::Weapon> {
-        self.get_weapon_item_mut(self.selected_weapon)
+    pub fn fire_selected_weapon(
+        &mut self,
+        commands: &mut Commands,
+        owner: Entity,
+        datum: &data::WeaponDatum,
+        time: &Time,
+        origin: &Transform,
+    ) -> bool {
+        let weapon_slot = self.selected_weapon;
+        let Some(weapon) = self.get_weapon_item_mut(weapon_slot) else {
+            return false;
+        };
+
+        let previous = weapon.ammo_count;
+        if !weapon.fire(commands, owner, datum, time, origin) {
+            return false;
+        }
+
+        let current = weapon.ammo_count;
+        self.changes.push(InventoryChange::AmmoChanged {
+            weapon_slot,
+            previous,
+            current,
+        });
+
+        true
     }
 
     fn set_weapon_item(&mut self, weapon_slot: WeaponSlot, weapon: weapon::Weapon) {
+        let weapon_type = weapon.r#type;
         match weapon_slot {
             WeaponSlot::Primary => {
                 info!("setting primary weapon {:?}", weapon);
@@ -125,19 +526,17 @@ impl Inventory {
                 info!("setting secondary weapon {:?}", weapon);
                 self.secondary = Some(weapon);
             }
+            WeaponSlot::Throwable => {
+                warn!("weapons can't go in the throwable slot");
+                return;
+            }
         }
         warn!("TODO: handle replace weapon");
-    }
-
-    fn set_selected_weapon_item(&mut self, weapon: weapon::Weapon) {
-        self.set_weapon_item(self.selected_weapon, weapon);
-    }
 
-    fn set_unselected_weapon_item(&mut self, weapon: weapon::Weapon) {
-        match self.selected_weapon {
-            WeaponSlot::Primary => self.set_weapon_item(WeaponSlot::Secondary, weapon),
-            WeaponSlot::Secondary => self.set_weapon_item(WeaponSlot::Primary, weapon),
-        }
+        self.changes.push(InventoryChange::WeaponEquipped(
+            weapon_slot,
+            Some(weapon_type),
+        ));
     }
 
     pub fn set_selected_weapon(&mut self, weapon_slot: WeaponSlot) {
@@ -146,44 +545,371 @@ impl Inventory {
             weapon_slot,
             self.get_weapon_item(weapon_slot).is_some()
         );
+
+        let previous = self.selected_weapon;
         self.selected_weapon = weapon_slot;
+
+        if previous != weapon_slot {
+            self.changes.push(InventoryChange::WeaponSlotChanged {
+                previous,
+                current: weapon_slot,
+            });
+        }
     }
 
     pub fn toggle_selected_weapon(&mut self) {
         match self.selected_weapon {
             WeaponSlot::Primary => self.set_selected_weapon(WeaponSlot::Secondary),
-            WeaponSlot::Secondary => self.set_selected_weapon(WeaponSlot::Primary),
+            WeaponSlot::Secondary | WeaponSlot::Throwable => {
+                self.set_selected_weapon(WeaponSlot::Primary)
+            }
+        }
+    }
+
+    pub fn take_selected_weapon(&mut self) -> Option<weapon::Weapon> {
+        let weapon = match self.selected_weapon {
+            WeaponSlot::Primary => self.primary.take(),
+            WeaponSlot::Secondary => self.secondary.take(),
+            WeaponSlot::Throwable => None,
+        }?;
+
+        self.changes
+            .push(InventoryChange::WeaponEquipped(self.selected_weapon, None));
+
+        Some(weapon)
+    }
+
+    fn set_stack_count(&mut self, slot: usize, count: usize) {
+        let item = self.items[slot];
+        self.items[slot] = item.with_count(count);
+
+        self.changes.push(InventoryChange::StackChanged {
+            item: self.items[slot],
+            previous_count: item.get_count(),
+        });
+    }
+
+    fn push_item(&mut self, item: InventoryItem) {
+        self.items.push(item);
+        self.changes.push(InventoryChange::ItemAdded(item));
+    }
+
+    fn remove_item(&mut self, slot: usize) -> InventoryItem {
+        let item = self.items.remove(slot);
+        self.changes.push(InventoryChange::ItemRemoved(item));
+        item
+    }
+
+    // removes count items from the stack in the slot
+    // or the whole stack if count is None (or covers the whole stack)
+    pub fn take_item(&mut self, slot: usize, count: Option<usize>) -> Option<InventoryItem> {
+        let Some(item) = self.items.get(slot).copied() else {
+            warn!("invalid inventory slot {}", slot);
+            return None;
+        };
+
+        let current = item.get_count();
+        match count {
+            Some(0) => None,
+            Some(count) if item.is_stackable() && count < current => {
+                self.set_stack_count(slot, current - count);
+                self.sort_items();
+
+                Some(item.with_count(count))
+            }
+            _ => Some(self.remove_item(slot)),
+        }
+    }
+
+    pub fn has_consumable(&self, consumable_type: data::ConsumableType) -> bool {
+        self.items.iter().any(
+            |item| matches!(item, InventoryItem::Consumable(r#type, _) if *r#type == consumable_type),
+        )
+    }
+
+    // takes one from the smallest stack so full stacks stay full
+    pub fn take_consumable(&mut self, consumable_type: data::ConsumableType) -> bool {
+        let slot = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                matches!(item, InventoryItem::Consumable(r#type, _) if *r#type == consumable_type)
+            })
+            .min_by_key(|(_, item)| item.get_count())
+            .map(|(slot, _)| slot);
+
+        let Some(slot) = slot else {
+            return false;
+        };
+
+        self.take_item(slot, Some(1)).is_some()
+    }
+
+    pub fn get_throwable(&self) -> Option<data::ThrowableType> {
+        self.items.iter().find_map(|item| match item {
+            InventoryItem::Throwable(throwable_type) => Some(*throwable_type),
+            _ => None,
+        })
+    }
+
+    pub fn take_throwable(&mut self) -> Option<data::ThrowableType> {
+        let idx = self
+            .items
+            .iter()
+            .position(|item| matches!(item, InventoryItem::Throwable(_)))?;
+
+        match self.remove_item(idx) {
+            InventoryItem::Throwable(throwable_type) => Some(throwable_type),
+            _ => unreachable!(),
         }
     }
 
-    pub fn add_item(&mut self, item: InventoryItem) -> bool {
+    pub fn add_item(
+        &mut self,
+        item: InventoryItem,
+        item_data: &ItemData,
+    ) -> Result<(), AddItemRejection> {
+        let reject = |reason| AddItemRejection {
+            reason,
+            remaining: item,
+        };
+
         match item {
             InventoryItem::Weapon(weapon_type, ammo_count) => {
-                if self.get_selected_weapon_item().is_none() {
-                    self.set_selected_weapon_item(weapon::Weapon::new(weapon_type, ammo_count));
-                    true
-                } else if self.get_unselected_weapon_item().is_none() {
-                    self.set_unselected_weapon_item(weapon::Weapon::new(weapon_type, ammo_count));
-                    true
+                let (selected, unselected) = self.get_weapon_slots();
+                if self.get_weapon_item(selected).is_none() {
+                    self.set_weapon_item(selected, weapon::Weapon::new(weapon_type, ammo_count));
+                    Ok(())
+                } else if self.get_weapon_item(unselected).is_none() {
+                    self.set_weapon_item(unselected, weapon::Weapon::new(weapon_type, ammo_count));
+                    Ok(())
                 } else {
                     warn!("TODO: hold to weapon swap");
-                    false
+                    Err(reject(RejectReason::WeaponSlotsFull))
                 }
             }
-            InventoryItem::Ammo(_, _) | InventoryItem::Throwable | InventoryItem::Consumable => {
-                if self.items.len() >= self.size {
-                    return false;
+            InventoryItem::Ammo(_, _) | InventoryItem::Consumable(_, _) => {
+                let result = self.stack_item(item, item_data);
+
+                self.sort_items();
+
+                result
+            }
+            InventoryItem::Throwable(_) => {
+                self.check_fit(&item, item_data).map_err(reject)?;
+
+                self.push_item(item);
+
+                self.sort_items();
+
+                Ok(())
+            }
+            InventoryItem::Armor(_) => {
+                warn!("armor can't be added to the inventory");
+                Err(reject(RejectReason::NotStorable))
+            }
+            InventoryItem::Backpack(_) => {
+                warn!("backpacks can't be added to the inventory");
+                Err(reject(RejectReason::NotStorable))
+            }
+        }
+    }
+
+    // tops up existing stacks before overflowing into new slots
+    fn stack_item(
+        &mut self,
+        item: InventoryItem,
+        item_data: &ItemData,
+    ) -> Result<(), AddItemRejection> {
+        let stack_size = item.get_stack_size(item_data).unwrap();
+        let mut remaining = item.get_count();
+        let mut rejected = None;
+
+        // weight limits apply to topping up stacks too
+        // so only take as much as we can carry
+        let mut overweight = 0;
+        if let data::InventoryCapacity::Weight(max_weight) = self.capacity {
+            let unit_weight = item.with_count(1).get_weight(item_data);
+            if unit_weight > 0.0 {
+                let available = (max_weight - self.get_weight(item_data)).max(0.0);
+                let carryable = ((available + WEIGHT_EPSILON) / unit_weight).floor() as usize;
+                if carryable < remaining {
+                    overweight = remaining - carryable;
+                    remaining = carryable;
+                    rejected = Some(RejectReason::Overweight);
                 }
+            }
+        }
 
-                warn!("TODO: stack inventory items");
+        for slot in 0..self.items.len() {
+            if remaining == 0 {
+                break;
+            }
 
-                self.items.push(item);
+            if !self.items[slot].stacks_with(&item) {
+                continue;
+            }
 
-                warn!("TODO: sort inventory items");
+            let count = self.items[slot].get_count();
+            let amount = stack_size.saturating_sub(count).min(remaining);
+            if amount > 0 {
+                self.set_stack_count(slot, count + amount);
+                remaining -= amount;
+            }
+        }
 
-                true
+        while remaining > 0 {
+            let amount = remaining.min(stack_size);
+            if let Err(reason) = self.check_fit(&item.with_count(amount), item_data) {
+                rejected = Some(reason);
+                break;
             }
+
+            self.push_item(item.with_count(amount));
+            remaining -= amount;
+        }
+
+        remaining += overweight;
+        if remaining == 0 {
+            return Ok(());
+        }
+
+        Err(AddItemRejection {
+            reason: rejected.unwrap(),
+            remaining: item.with_count(remaining),
+        })
+    }
+}
+
+// first fit packing, biggest footprints first
+// returns the grid position of each footprint if they all fit
+fn pack_grid(size: UVec2, footprints: &[UVec2]) -> Option<Vec<UVec2>> {
+    let mut occupied = vec![false; (size.x * size.y) as usize];
+    let mut positions = vec![UVec2::ZERO; footprints.len()];
+
+    let mut order = (0..footprints.len()).collect::<Vec<_>>();
+    order.sort_by_key(|idx| Reverse(footprints[*idx].element_product()));
+
+    let cells = |position: UVec2, footprint: UVec2| {
+        (position.y..position.y + footprint.y)
+            .flat_map(move |y| (position.x..position.x + footprint.x).map(move |x| (x, y)))
+    };
+
+    for idx in order {
+        let footprint = footprints[idx];
+        if footprint.x > size.x || footprint.y > size.y {
+            return None;
         }
+
+        let position = (0..=size.y - footprint.y)
+            .flat_map(|y| (0..=size.x - footprint.x).map(move |x| UVec2::new(x, y)))
+            .find(|position| {
+                cells(*position, footprint).all(|(x, y)| !occupied[(y * size.x + x) as usize])
+            })?;
+
+        for (x, y) in cells(position, footprint) {
+            occupied[(y * size.x + x) as usize] = true;
+        }
+        positions[idx] = position;
+    }
+
+    Some(positions)
+}
+
+#[derive(Debug, Event)]
+pub struct PickupRejectedEvent {
+    pub item: InventoryItem,
+    pub reason: RejectReason,
+}
+
+#[derive(Debug, Event)]
+pub struct ItemAddedEvent {
+    pub item: InventoryItem,
+}
+
+#[derive(Debug, Event)]
+pub struct ItemRemovedEvent {
+    pub item: InventoryItem,
+}
+
+#[derive(Debug, Event)]
+pub struct StackChangedEvent {
+    // the stack after the change
+    pub item: InventoryItem,
+    pub previous_count: usize,
+}
+
+// items were sorted or moved between slots
+#[derive(Debug, Event)]
+pub struct ItemsRearrangedEvent;
+
+// a backpack was equipped
+#[derive(Debug, Event)]
+pub struct CapacityChangedEvent;
+
+#[derive(Debug, Event)]
+pub struct WeaponEquippedEvent {
+    pub weapon_slot: WeaponSlot,
+    // None if the weapon was removed from the slot
+    pub weapon_type: Option<data::WeaponType>,
+}
+
+#[derive(Debug, Event)]
+pub struct WeaponSlotChangedEvent {
+    pub previous: WeaponSlot,
+    pub current: WeaponSlot,
+}
+
+#[derive(Debug, Event)]
+pub struct AmmoChangedEvent {
+    pub weapon_slot: WeaponSlot,
+    pub previous: usize,
+    pub current: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct DropItemEvent {
+    pub slot: usize,
+    // drops the whole stack if None
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Event)]
+pub struct DropWeaponEvent;
+
+#[derive(Debug, Event)]
+pub struct UseItemEvent {
+    pub slot: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct SetSortModeEvent {
+    pub sort_mode: SortMode,
+}
+
+#[derive(Debug, Event)]
+pub struct SwapSlotsEvent {
+    pub a: usize,
+    pub b: usize,
+}
+
+#[derive(Debug, Event)]
+pub struct SplitStackEvent {
+    pub slot: usize,
+    pub count: usize,
+}
+
+// convenience for things (like the HUD) that only care about the local player
+#[derive(SystemParam)]
+pub struct LocalInventory<'w, 's> {
+    inventory_query: Query<'w, 's, &'static Inventory, With<player::LocalPlayer>>,
+}
+
+impl LocalInventory<'_, '_> {
+    // only returns the inventory if the entity is the local player
+    pub fn get(&self, entity: Entity) -> Option<&Inventory> {
+        self.inventory_query.get(entity).ok()
     }
 }
 
@@ -192,7 +918,305 @@ pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Inventory>()
+        app.add_systems(PostUpdate, emit_inventory_events)
+            .add_observer(on_drop_item)
+            .add_observer(on_drop_weapon)
+            .add_observer(on_use_item)
+            .add_observer(on_set_sort_mode)
+            .add_observer(on_swap_slots)
+            .add_observer(on_split_stack)
             .register_type::<Inventory>();
     }
 }
+
+fn emit_inventory_events(
+    mut commands: Commands,
+    mut inventory_query: Query<(Entity, &mut Inventory)>,
+) {
+    for (entity, mut inventory) in inventory_query.iter_mut() {
+        // don't trip change detection if nothing changed
+        if inventory.changes.is_empty() {
+            continue;
+        }
+
+        for change in inventory.changes.drain(..) {
+            match change {
+                InventoryChange::ItemAdded(item) => {
+                    commands.trigger_targets(ItemAddedEvent { item }, entity);
+                }
+                InventoryChange::ItemRemoved(item) => {
+                    commands.trigger_targets(ItemRemovedEvent { item }, entity);
+                }
+                InventoryChange::StackChanged {
+                    item,
+                    previous_count,
+                } => {
+                    commands.trigger_targets(
+                        StackChangedEvent {
+                            item,
+                            previous_count,
+                        },
+                        entity,
+                    );
+                }
+                InventoryChange::ItemsRearranged => {
+                    commands.trigger_targets(ItemsRearrangedEvent, entity);
+                }
+                InventoryChange::CapacityChanged => {
+                    commands.trigger_targets(CapacityChangedEvent, entity);
+                }
+                InventoryChange::WeaponEquipped(weapon_slot, weapon_type) => {
+                    commands.trigger_targets(
+                        WeaponEquippedEvent {
+                            weapon_slot,
+                            weapon_type,
+                        },
+                        entity,
+                    );
+                }
+                InventoryChange::WeaponSlotChanged { previous, current } => {
+                    commands.trigger_targets(WeaponSlotChangedEvent { previous, current }, entity);
+                }
+                InventoryChange::AmmoChanged {
+                    weapon_slot,
+                    previous,
+                    current,
+                } => {
+                    commands.trigger_targets(
+                        AmmoChangedEvent {
+                            weapon_slot,
+                            previous,
+                            current,
+                        },
+                        entity,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn on_drop_item(
+    trigger: Trigger<DropItemEvent>,
+    mut commands: Commands,
+    game_assets: Res<assets::GameAssets>,
+    mut inventory_query: Query<(&mut Inventory, &GlobalTransform)>,
+) {
+    let Ok((mut inventory, global_transform)) = inventory_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    let Some(item) = inventory.take_item(trigger.slot, trigger.count) else {
+        return;
+    };
+
+    loot::drop_ground_loot(&mut commands, &game_assets, item, global_transform);
+}
+
+fn on_drop_weapon(
+    trigger: Trigger<DropWeaponEvent>,
+    mut commands: Commands,
+    game_assets: Res<assets::GameAssets>,
+    mut inventory_query: Query<(&mut Inventory, &GlobalTransform)>,
+) {
+    let Ok((mut inventory, global_transform)) = inventory_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    let Some(weapon) = inventory.take_selected_weapon() else {
+        return;
+    };
+
+    loot::drop_ground_loot(
+        &mut commands,
+        &game_assets,
+        InventoryItem::Weapon(weapon.r#type, weapon.ammo_count),
+        global_transform,
+    );
+}
+
+fn on_use_item(
+    trigger: Trigger<UseItemEvent>,
+    mut commands: Commands,
+    inventory_query: Query<&Inventory>,
+) {
+    let Ok(inventory) = inventory_query.get(trigger.entity()) else {
+        return;
+    };
+
+    match inventory.get_items().get(trigger.slot) {
+        Some(InventoryItem::Consumable(consumable_type, _)) => {
+            commands.trigger_targets(
+                consumable::UseConsumableEvent {
+                    consumable_type: *consumable_type,
+                },
+                trigger.entity(),
+            );
+        }
+        Some(item) => warn!("can't use inventory item {:?}", item),
+        None => warn!("invalid inventory slot {}", trigger.slot),
+    }
+}
+
+fn on_set_sort_mode(
+    trigger: Trigger<SetSortModeEvent>,
+    mut inventory_query: Query<&mut Inventory>,
+) {
+    let Ok(mut inventory) = inventory_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    inventory.set_sort_mode(trigger.sort_mode);
+}
+
+fn on_swap_slots(trigger: Trigger<SwapSlotsEvent>, mut inventory_query: Query<&mut Inventory>) {
+    let Ok(mut inventory) = inventory_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    inventory.swap_slots(trigger.a, trigger.b);
+}
+
+fn on_split_stack(
+    trigger: Trigger<SplitStackEvent>,
+    item_data: ItemData,
+    mut inventory_query: Query<&mut Inventory>,
+) {
+    let Ok(mut inventory) = inventory_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    inventory.split_stack(trigger.slot, trigger.count, &item_data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn counts(inventory: &Inventory) -> Vec<usize> {
+        let mut counts = inventory
+            .get_items()
+            .iter()
+            .map(InventoryItem::get_count)
+            .collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts
+    }
+
+    #[test]
+    fn tops_up_existing_stack() {
+        let mut world = test_utils::create_world();
+        test_utils::with_item_data(&mut world, |item_data| {
+            let mut inventory = Inventory::new(data::InventoryCapacity::Slots(5));
+            inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 30), item_data)
+                .unwrap();
+            inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 20), item_data)
+                .unwrap();
+
+            assert_eq!(
+                inventory.get_items(),
+                &vec![InventoryItem::Ammo(data::AmmoType::Light, 50)]
+            );
+        });
+    }
+
+    #[test]
+    fn overflows_into_new_slots() {
+        let mut world = test_utils::create_world();
+        test_utils::with_item_data(&mut world, |item_data| {
+            let mut inventory = Inventory::new(data::InventoryCapacity::Slots(5));
+            inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 30), item_data)
+                .unwrap();
+            inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 90), item_data)
+                .unwrap();
+
+            assert_eq!(counts(&inventory), vec![50, 50, 20]);
+        });
+    }
+
+    #[test]
+    fn rejects_when_full() {
+        let mut world = test_utils::create_world();
+        test_utils::with_item_data(&mut world, |item_data| {
+            let mut inventory = Inventory::new(data::InventoryCapacity::Slots(1));
+            inventory
+                .add_item(
+                    InventoryItem::Throwable(data::ThrowableType::Grenade),
+                    item_data,
+                )
+                .unwrap();
+
+            let rejection = inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 10), item_data)
+                .unwrap_err();
+
+            assert_eq!(rejection.reason, RejectReason::Full);
+            assert_eq!(
+                rejection.remaining,
+                InventoryItem::Ammo(data::AmmoType::Light, 10)
+            );
+            assert_eq!(
+                inventory.get_items(),
+                &vec![InventoryItem::Throwable(data::ThrowableType::Grenade)]
+            );
+        });
+    }
+
+    #[test]
+    fn returns_partial_remainder() {
+        let mut world = test_utils::create_world();
+        test_utils::with_item_data(&mut world, |item_data| {
+            let mut inventory = Inventory::new(data::InventoryCapacity::Slots(1));
+            inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 30), item_data)
+                .unwrap();
+
+            let rejection = inventory
+                .add_item(InventoryItem::Ammo(data::AmmoType::Light, 40), item_data)
+                .unwrap_err();
+
+            assert_eq!(rejection.reason, RejectReason::Full);
+            assert_eq!(
+                rejection.remaining,
+                InventoryItem::Ammo(data::AmmoType::Light, 20)
+            );
+            assert_eq!(
+                inventory.get_items(),
+                &vec![InventoryItem::Ammo(data::AmmoType::Light, 50)]
+            );
+        });
+    }
+
+    #[test]
+    fn stacks_consumables() {
+        let mut world = test_utils::create_world();
+        test_utils::with_item_data(&mut world, |item_data| {
+            let mut inventory = Inventory::new(data::InventoryCapacity::Slots(5));
+            for _ in 0..2 {
+                inventory
+                    .add_item(
+                        InventoryItem::Consumable(data::ConsumableType::MedKit, 2),
+                        item_data,
+                    )
+                    .unwrap();
+            }
+            inventory
+                .add_item(
+                    InventoryItem::Consumable(data::ConsumableType::Bandage, 3),
+                    item_data,
+                )
+                .unwrap();
+
+            let items = inventory.get_items();
+            assert_eq!(items.len(), 3);
+            assert!(items.contains(&InventoryItem::Consumable(data::ConsumableType::MedKit, 3)));
+            assert!(items.contains(&InventoryItem::Consumable(data::ConsumableType::MedKit, 1)));
+            assert!(items.contains(&InventoryItem::Consumable(data::ConsumableType::Bandage, 3)));
+        });
+    }
+}
@@ -1,9 +1,12 @@
-use avian3d::prelude::*;
-use bevy::{color::palettes::css, prelude::*};
+use std::{collections::HashMap, time::Duration};
 
-use crate::{
-    GameCollisionLayers, LOOT_INTERACT_LAYERS, RandomSource, assets, data, interactables, inventory,
+use avian3d::prelude::*;
+use bevy::{
+    color::palettes::css, ecs::system::SystemParam, prelude::*, time::common_conditions::on_timer,
 };
+use strum::IntoEnumIterator;
+
+use crate::{GameCollisionLayers, LOOT_INTERACT_LAYERS, assets, data, interactables, inventory};
 
 #[derive(Debug, Component)]
 #[require(Transform)]
@@ -11,6 +14,11 @@ pub struct Bobber {
     bob_amp: f32,
     bob_speed: f32,
     rot_speed: f32,
+    // spread out so nearby bobbers don't move in lockstep
+    // derived from the entity rather than rolled so seeded runs are reproducible
+    phase: Option<f32>,
+    // bobbing is relative to where the bobber started
+    rest_height: Option<f32>,
 }
 
 impl Default for Bobber {
@@ -19,61 +27,436 @@ impl Default for Bobber {
             bob_amp: 0.5,
             bob_speed: 1.25,
             rot_speed: 0.25,
+            phase: None,
+            rest_height: None,
         }
     }
 }
 
+// golden angle, so consecutive entities get well spread out phases
+const BOB_PHASE_STEP: f32 = 2.399_963;
+
 const WEAPON_RADIUS: f32 = 0.25;
 const WEAPON_LENGTH: f32 = 0.5;
 const AMMO_LENGTH: f32 = 0.5;
 const THROWABLE_RADIUS: f32 = 0.2;
 const CONSUMABLE_RADIUS: f32 = 0.2;
+const ARMOR_LENGTH: f32 = 0.6;
+const BACKPACK_LENGTH: f32 = 0.5;
+
+// the model bobs above the rarity ring
+const MODEL_REST_HEIGHT: f32 = 0.5;
+const RARITY_RING_INNER_RADIUS: f32 = 0.4;
+const RARITY_RING_OUTER_RADIUS: f32 = 0.5;
+const RARITY_RING_EMISSIVE: f32 = 4.0;
+
+// dropped loot is tossed out in front of whoever dropped it
+const DROP_DISTANCE: f32 = 1.0;
+const DROP_HEIGHT: f32 = 1.0;
+const DROP_IMPULSE: f32 = 0.5;
+const DROP_RESTITUTION: f32 = 0.3;
 
-#[derive(Debug, Deref, Component, Reflect)]
+// dropped loot is considered settled after staying slow for long enough
+const SETTLE_SPEED: f32 = 0.1;
+const SETTLE_DURATION: f32 = 0.5;
+// don't let jittery loot simulate forever
+const SETTLE_TIMEOUT: f32 = 5.0;
+
+// how often ground ammo is merged and the ground loot cap is enforced
+const CLEANUP_INTERVAL: f32 = 1.0;
+
+#[derive(Debug, Resource, Reflect)]
+pub struct GroundLootSettings {
+    // ammo piles of the same type this close together are merged
+    pub merge_radius: f32,
+    // the lowest value, oldest loot is despawned past this (spawned loot doesn't count)
+    pub max_ground_loot: usize,
+}
+
+impl Default for GroundLootSettings {
+    fn default() -> Self {
+        Self {
+            merge_radius: 1.5,
+            max_ground_loot: 100,
+        }
+    }
+}
+
+// which item categories are picked up just by walking over them
+#[derive(Debug, Resource, Reflect)]
+pub struct AutoPickupSettings {
+    // ammo is only picked up if a held weapon uses it
+    pub ammo: bool,
+    pub throwable: bool,
+    pub consumable: bool,
+}
+
+impl Default for AutoPickupSettings {
+    fn default() -> Self {
+        Self {
+            ammo: true,
+            throwable: false,
+            consumable: false,
+        }
+    }
+}
+
+impl AutoPickupSettings {
+    pub fn is_enabled(&self, category: inventory::ItemCategory) -> bool {
+        match category {
+            inventory::ItemCategory::Ammo => self.ammo,
+            inventory::ItemCategory::Throwable => self.throwable,
+            inventory::ItemCategory::Consumable => self.consumable,
+            // equipment always has to be interacted with
+            inventory::ItemCategory::Weapon
+            | inventory::ItemCategory::Armor
+            | inventory::ItemCategory::Backpack => false,
+        }
+    }
+}
+
+// the item data needed to load ground loot visuals
+#[derive(SystemParam)]
+pub struct LootData<'w> {
+    pub weapon: Res<'w, data::WeaponDataSource>,
+    pub ammo: Res<'w, data::AmmoDataSource>,
+    pub throwable: Res<'w, data::ThrowableDataSource>,
+    pub consumable: Res<'w, data::ConsumableDataSource>,
+    pub armor: Res<'w, data::ArmorDataSource>,
+    pub backpack: Res<'w, data::BackpackDataSource>,
+}
+
+impl LootData<'_> {
+    pub fn get_rarity(&self, item: &inventory::InventoryItem) -> data::Rarity {
+        match item {
+            inventory::InventoryItem::Weapon(weapon_type, _) => {
+                self.weapon.get(weapon_type).unwrap().rarity
+            }
+            inventory::InventoryItem::Ammo(ammo_type, _) => {
+                self.ammo.get(ammo_type).unwrap().rarity
+            }
+            inventory::InventoryItem::Throwable(throwable_type) => {
+                self.throwable.get(throwable_type).unwrap().rarity
+            }
+            inventory::InventoryItem::Consumable(consumable_type, _) => {
+                self.consumable.get(consumable_type).unwrap().rarity
+            }
+            inventory::InventoryItem::Armor(armor_type) => {
+                self.armor.get(armor_type).unwrap().rarity
+            }
+            inventory::InventoryItem::Backpack(backpack_type) => {
+                self.backpack.get(backpack_type).unwrap().rarity
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deref, DerefMut, Component, Reflect)]
 pub struct GroundLoot(inventory::InventoryItem);
 
+impl GroundLoot {
+    pub fn new(item: inventory::InventoryItem) -> Self {
+        Self(item)
+    }
+}
+
+// when the loot hit the ground, for culling the oldest loot first
+#[derive(Debug, Component)]
+struct GroundLootSpawnTime(f32);
+
+#[derive(Debug)]
+struct LootPile {
+    entity: Entity,
+    position: Vec3,
+    item: inventory::InventoryItem,
+}
+
+// loot still sitting on the spawn that placed it
+// cleanup leaves it alone so the spawn can tell when it's actually been taken
+#[derive(Debug, Component)]
+pub struct SpawnedLoot;
+
 #[derive(Debug, Component)]
 #[require(Bobber)]
 pub struct GroundLootModel;
 
+// dropped loot is dynamic until it comes to rest
+// and doesn't bob until then
+#[derive(Debug, Component)]
+pub struct Settling {
+    rest_timer: Timer,
+    timeout_timer: Timer,
+}
+
+impl Default for Settling {
+    fn default() -> Self {
+        Self {
+            rest_timer: Timer::from_seconds(SETTLE_DURATION, TimerMode::Once),
+            timeout_timer: Timer::from_seconds(SETTLE_TIMEOUT, TimerMode::Once),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GroundLootPlugin;
 
 impl Plugin for GroundLootPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, animate_bobbers)
-            .register_type::<GroundLoot>();
+        app.add_systems(
+            Update,
+            (
+                (update_settling, animate_bobbers).chain(),
+                stamp_ground_loot,
+                (merge_ground_ammo, cap_ground_loot)
+                    .chain()
+                    .run_if(on_timer(Duration::from_secs_f32(CLEANUP_INTERVAL))),
+            ),
+        )
+        .init_resource::<AutoPickupSettings>()
+        .register_type::<AutoPickupSettings>()
+        .init_resource::<GroundLootSettings>()
+        .register_type::<GroundLootSettings>()
+        .register_type::<GroundLoot>();
+    }
+}
+
+fn update_settling(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut settling_query: Query<(Entity, &LinearVelocity, &mut Settling)>,
+) {
+    for (entity, linear_velocity, mut settling) in settling_query.iter_mut() {
+        settling.timeout_timer.tick(time.delta());
+
+        if linear_velocity.length() < SETTLE_SPEED {
+            settling.rest_timer.tick(time.delta());
+        } else {
+            settling.rest_timer.reset();
+        }
+
+        if settling.rest_timer.finished() || settling.timeout_timer.finished() {
+            info!("ground loot {} settled", entity);
+            commands
+                .entity(entity)
+                .remove::<Settling>()
+                .insert((RigidBody::Static, LinearVelocity::ZERO));
+        }
+    }
+}
+
+fn stamp_ground_loot(
+    mut commands: Commands,
+    time: Res<Time>,
+    ground_loot_query: Query<Entity, Added<GroundLoot>>,
+) {
+    for entity in ground_loot_query.iter() {
+        commands
+            .entity(entity)
+            .insert(GroundLootSpawnTime(time.elapsed_secs()));
+    }
+}
+
+// tops up stacks from nearby piles of the same type, oldest piles first
+// returns the piles that were emptied
+fn merge_loot_piles(
+    piles: &mut [LootPile],
+    merge_radius: f32,
+    item_data: &inventory::ItemData,
+) -> Vec<Entity> {
+    let mut emptied = vec![];
+    for idx in 0..piles.len() {
+        let Some(stack_size) = piles[idx].item.get_stack_size(item_data) else {
+            continue;
+        };
+
+        if piles[idx].item.get_count() == 0 {
+            continue;
+        }
+
+        for other in idx + 1..piles.len() {
+            let count = piles[idx].item.get_count();
+            if count >= stack_size {
+                break;
+            }
+
+            let other_count = piles[other].item.get_count();
+            if other_count == 0
+                || !piles[other].item.stacks_with(&piles[idx].item)
+                || piles[other].position.distance(piles[idx].position) > merge_radius
+            {
+                continue;
+            }
+
+            let amount = (stack_size - count).min(other_count);
+            piles[idx].item = piles[idx].item.with_count(count + amount);
+            piles[other].item = piles[other].item.with_count(other_count - amount);
+            if other_count == amount {
+                emptied.push(piles[other].entity);
+            }
+        }
+    }
+
+    emptied
+}
+
+fn merge_ground_ammo(
+    mut commands: Commands,
+    settings: Res<GroundLootSettings>,
+    item_data: inventory::ItemData,
+    mut ground_loot_query: Query<
+        (
+            Entity,
+            &mut GroundLoot,
+            &GlobalTransform,
+            &GroundLootSpawnTime,
+        ),
+        (Without<Settling>, Without<SpawnedLoot>),
+    >,
+) {
+    let mut piles = ground_loot_query
+        .iter()
+        .filter(|(_, loot, _, _)| matches!(***loot, inventory::InventoryItem::Ammo(_, _)))
+        .map(|(entity, loot, global_transform, spawn_time)| {
+            (
+                spawn_time.0,
+                LootPile {
+                    entity,
+                    position: global_transform.translation(),
+                    item: **loot,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    piles.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut piles = piles.into_iter().map(|(_, pile)| pile).collect::<Vec<_>>();
+
+    let emptied = merge_loot_piles(&mut piles, settings.merge_radius, &item_data);
+    for pile in piles.iter().filter(|pile| pile.item.get_count() > 0) {
+        let mut loot = ground_loot_query.get_mut(pile.entity).unwrap().1;
+        if **loot != pile.item {
+            **loot = pile.item;
+        }
+    }
+
+    for entity in emptied {
+        info!("merged ground ammo {}", entity);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn cap_ground_loot(
+    mut commands: Commands,
+    settings: Res<GroundLootSettings>,
+    loot_data: LootData,
+    ground_loot_query: Query<(Entity, &GroundLoot, &GroundLootSpawnTime), Without<SpawnedLoot>>,
+) {
+    let count = ground_loot_query.iter().count();
+    if count <= settings.max_ground_loot {
+        return;
+    }
+
+    // lowest rarity first, then oldest first
+    let mut ground_loot = ground_loot_query
+        .iter()
+        .map(|(entity, loot, spawn_time)| {
+            (entity, loot_data.get_rarity(loot) as usize, spawn_time.0)
+        })
+        .collect::<Vec<_>>();
+    ground_loot.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+    for (entity, _, _) in ground_loot.iter().take(count - settings.max_ground_loot) {
+        info!("ground loot cap reached, despawning {}", entity);
+        commands.entity(*entity).despawn_recursive();
     }
 }
 
-fn animate_bobbers(time: Res<Time>, mut bobber_query: Query<(&Bobber, &mut Transform)>) {
-    for (bobber, mut transform) in bobber_query.iter_mut() {
-        transform.translation.y = bobber.bob_amp
+fn animate_bobbers(
+    time: Res<Time>,
+    mut bobber_query: Query<(Entity, &mut Bobber, &mut Transform, Option<&Parent>)>,
+    settling_query: Query<(), With<Settling>>,
+) {
+    for (entity, mut bobber, mut transform, parent) in bobber_query.iter_mut() {
+        if parent.is_some_and(|parent| settling_query.contains(parent.get())) {
+            continue;
+        }
+
+        let rest_height = *bobber.rest_height.get_or_insert(transform.translation.y);
+        let phase = *bobber.phase.get_or_insert_with(|| {
+            (entity.index() as f32 * BOB_PHASE_STEP).rem_euclid(std::f32::consts::TAU)
+        });
+
+        transform.translation.y = rest_height
             + bobber.bob_amp
-                * (time.elapsed_secs() * bobber.bob_speed * std::f32::consts::FRAC_PI_2).sin();
+                * (time.elapsed_secs() * bobber.bob_speed * std::f32::consts::FRAC_PI_2 + phase)
+                    .sin();
 
         transform.rotate_y(std::f32::consts::TAU * time.delta_secs() * bobber.rot_speed);
     }
 }
 
-pub fn load_weapon_assets(
+pub fn load_loot_model_assets(
+    asset_server: &AssetServer,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
-) -> assets::MeshMaterial {
-    assets::MeshMaterial {
-        mesh: meshes.add(Capsule3d::new(WEAPON_RADIUS, WEAPON_LENGTH)),
-        material: materials.add(Color::from(css::DARK_RED)),
+    model: &data::LootModel,
+) -> assets::LootModelAsset {
+    match model {
+        data::LootModel::Scene(path) => assets::LootModelAsset::Scene(
+            asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.clone())),
+        ),
+        data::LootModel::Capsule {
+            radius,
+            length,
+            color,
+        } => assets::LootModelAsset::Mesh(assets::MeshMaterial {
+            mesh: meshes.add(Capsule3d::new(*radius, *length)),
+            material: materials.add(Color::from(*color)),
+        }),
+        data::LootModel::Cuboid { size, color } => {
+            assets::LootModelAsset::Mesh(assets::MeshMaterial {
+                mesh: meshes.add(Cuboid::from_size(*size)),
+                material: materials.add(Color::from(*color)),
+            })
+        }
     }
 }
 
-pub fn load_ammo_assets(
+fn get_rarity_color(rarity: data::Rarity) -> Srgba {
+    match rarity {
+        data::Rarity::Common => css::LIGHT_GRAY,
+        data::Rarity::Uncommon => css::LIME,
+        data::Rarity::Rare => css::DODGER_BLUE,
+        data::Rarity::Epic => css::DARK_VIOLET,
+    }
+}
+
+pub fn load_rarity_ring_assets(
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
-) -> assets::MeshMaterial {
-    assets::MeshMaterial {
-        mesh: meshes.add(Cuboid::new(AMMO_LENGTH, AMMO_LENGTH, AMMO_LENGTH)),
-        material: materials.add(Color::from(css::GREEN_YELLOW)),
-    }
+) -> HashMap<data::Rarity, assets::MeshMaterial> {
+    let mesh = meshes.add(Torus::new(
+        RARITY_RING_INNER_RADIUS,
+        RARITY_RING_OUTER_RADIUS,
+    ));
+
+    data::Rarity::iter()
+        .map(|rarity| {
+            let color = get_rarity_color(rarity);
+            let material = materials.add(StandardMaterial {
+                base_color: color.into(),
+                emissive: LinearRgba::from(color) * RARITY_RING_EMISSIVE,
+                ..default()
+            });
+
+            (
+                rarity,
+                assets::MeshMaterial {
+                    mesh: mesh.clone(),
+                    material,
+                },
+            )
+        })
+        .collect()
 }
 
 pub fn load_throwable_assets(
@@ -96,40 +479,60 @@ pub fn load_consumable_assets(
     }
 }
 
-pub fn spawn_ground_loot(
-    commands: &mut Commands,
-    game_assets: &assets::GameAssets,
-    weapon_datum: &data::WeaponDatum,
-    ammo_datum: &data::AmmoDatum,
-    random: &mut RandomSource,
-    spawn_transform: &GlobalTransform,
-) {
-    let item = inventory::InventoryItem::random_loot(random, weapon_datum, ammo_datum);
+pub fn load_armor_assets(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> assets::MeshMaterial {
+    assets::MeshMaterial {
+        mesh: meshes.add(Cuboid::new(ARMOR_LENGTH, ARMOR_LENGTH, ARMOR_LENGTH * 0.5)),
+        material: materials.add(Color::from(css::STEEL_BLUE)),
+    }
+}
 
-    let (model, collider) = match item {
-        inventory::InventoryItem::Weapon(_, _) => (
-            game_assets.gen_weapon_mesh_components(),
-            Collider::capsule(WEAPON_RADIUS, WEAPON_LENGTH),
-        ),
-        inventory::InventoryItem::Ammo(_, _) => (
-            game_assets.gen_ammo_mesh_components(),
-            Collider::cuboid(AMMO_LENGTH, AMMO_LENGTH, AMMO_LENGTH),
-        ),
-        inventory::InventoryItem::Throwable => (
-            game_assets.gen_throwable_mesh_components(),
-            Collider::sphere(THROWABLE_RADIUS),
-        ),
-        inventory::InventoryItem::Consumable => (
-            game_assets.gen_consumable_mesh_components(),
-            Collider::sphere(CONSUMABLE_RADIUS),
+pub fn load_backpack_assets(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> assets::MeshMaterial {
+    assets::MeshMaterial {
+        mesh: meshes.add(Cuboid::new(
+            BACKPACK_LENGTH * 0.8,
+            BACKPACK_LENGTH,
+            BACKPACK_LENGTH * 0.5,
+        )),
+        material: materials.add(Color::from(css::SADDLE_BROWN)),
+    }
+}
+
+pub fn spawn_ground_loot<'a>(
+    commands: &'a mut Commands,
+    game_assets: &assets::GameAssets,
+    item: inventory::InventoryItem,
+    transform: Transform,
+) -> EntityCommands<'a> {
+    // TODO: colliders should come from the model
+    let collider = match item {
+        inventory::InventoryItem::Weapon(_, _) => Collider::capsule(WEAPON_RADIUS, WEAPON_LENGTH),
+        inventory::InventoryItem::Ammo(_, _) => {
+            Collider::cuboid(AMMO_LENGTH, AMMO_LENGTH, AMMO_LENGTH)
+        }
+        inventory::InventoryItem::Throwable(_) => Collider::sphere(THROWABLE_RADIUS),
+        inventory::InventoryItem::Consumable(_, _) => Collider::sphere(CONSUMABLE_RADIUS),
+        inventory::InventoryItem::Armor(_) => {
+            Collider::cuboid(ARMOR_LENGTH, ARMOR_LENGTH, ARMOR_LENGTH * 0.5)
+        }
+        inventory::InventoryItem::Backpack(_) => Collider::cuboid(
+            BACKPACK_LENGTH * 0.8,
+            BACKPACK_LENGTH,
+            BACKPACK_LENGTH * 0.5,
         ),
     };
+    let loot_asset = game_assets.get_loot_asset(&item).clone();
 
     let mut commands = commands.spawn((
-        spawn_transform.compute_transform(),
+        transform,
         Visibility::default(),
         Name::new("Ground Loot"),
-        GroundLoot(item),
+        GroundLoot::new(item),
     ));
 
     commands.insert((
@@ -140,8 +543,221 @@ pub fn spawn_ground_loot(
     ));
 
     commands.with_children(|parent| {
-        parent.spawn((model, Name::new("Model"), GroundLootModel));
+        let mut model = parent.spawn((
+            Transform::from_xyz(0.0, MODEL_REST_HEIGHT, 0.0),
+            Visibility::default(),
+            Name::new("Model"),
+            GroundLootModel,
+        ));
+        match loot_asset.model {
+            assets::LootModelAsset::Scene(scene) => {
+                model.insert(SceneRoot(scene));
+            }
+            assets::LootModelAsset::Mesh(mesh) => {
+                model.insert(mesh.gen_components());
+            }
+        }
+
+        parent.spawn((
+            loot_asset.rarity_ring.gen_components(),
+            Name::new("Rarity Ring"),
+        ));
 
-        interactables::spawn_interactable(parent, interactables::InteractableType::GroundLoot);
+        // the sensor shouldn't weigh down dropped loot
+        interactables::spawn_interactable(parent, interactables::InteractableType::GroundLoot)
+            .insert(ColliderDensity(0.0));
     });
+
+    commands
+}
+
+pub fn drop_ground_loot(
+    commands: &mut Commands,
+    game_assets: &assets::GameAssets,
+    item: inventory::InventoryItem,
+    dropper_transform: &GlobalTransform,
+) {
+    info!("dropping ground loot {:?}", item);
+
+    let forward = dropper_transform.forward();
+    let position =
+        dropper_transform.translation() + forward * DROP_DISTANCE + Vec3::Y * DROP_HEIGHT;
+
+    spawn_ground_loot(
+        commands,
+        game_assets,
+        item,
+        Transform::from_translation(position),
+    )
+    .insert((
+        RigidBody::Dynamic,
+        ExternalImpulse::new((forward + Vec3::Y) * DROP_IMPULSE),
+        Restitution::new(DROP_RESTITUTION),
+        Settling::default(),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::test_utils;
+
+    fn ammo(count: usize) -> inventory::InventoryItem {
+        inventory::InventoryItem::Ammo(data::AmmoType::Light, count)
+    }
+
+    fn pile(world: &mut World, position: Vec3, item: inventory::InventoryItem) -> LootPile {
+        LootPile {
+            entity: world.spawn_empty().id(),
+            position,
+            item,
+        }
+    }
+
+    #[test]
+    fn merges_same_type_within_radius() {
+        let mut world = test_utils::create_world();
+        let mut piles = vec![
+            pile(&mut world, Vec3::ZERO, ammo(10)),
+            pile(&mut world, Vec3::X, ammo(15)),
+        ];
+
+        let emptied = test_utils::with_item_data(&mut world, |item_data| {
+            merge_loot_piles(&mut piles, 1.5, item_data)
+        });
+
+        assert_eq!(emptied, vec![piles[1].entity]);
+        assert_eq!(piles[0].item, ammo(25));
+        assert_eq!(piles[1].item, ammo(0));
+    }
+
+    #[test]
+    fn does_not_merge_different_types() {
+        let mut world = test_utils::create_world();
+        let bandages = inventory::InventoryItem::Consumable(data::ConsumableType::Bandage, 3);
+        let mut piles = vec![
+            pile(&mut world, Vec3::ZERO, ammo(10)),
+            pile(&mut world, Vec3::X, bandages),
+        ];
+
+        let emptied = test_utils::with_item_data(&mut world, |item_data| {
+            merge_loot_piles(&mut piles, 1.5, item_data)
+        });
+
+        assert!(emptied.is_empty());
+        assert_eq!(piles[0].item, ammo(10));
+        assert_eq!(piles[1].item, bandages);
+    }
+
+    #[test]
+    fn does_not_merge_out_of_radius() {
+        let mut world = test_utils::create_world();
+        let mut piles = vec![
+            pile(&mut world, Vec3::ZERO, ammo(10)),
+            pile(&mut world, Vec3::X * 2.0, ammo(15)),
+        ];
+
+        let emptied = test_utils::with_item_data(&mut world, |item_data| {
+            merge_loot_piles(&mut piles, 1.5, item_data)
+        });
+
+        assert!(emptied.is_empty());
+        assert_eq!(piles[0].item, ammo(10));
+        assert_eq!(piles[1].item, ammo(15));
+    }
+
+    #[test]
+    fn merges_up_to_stack_size() {
+        let mut world = test_utils::create_world();
+        let stack_size = world
+            .resource::<data::AmmoDataSource>()
+            .get(&data::AmmoType::Light)
+            .unwrap()
+            .stack_size;
+        let mut piles = vec![
+            pile(&mut world, Vec3::ZERO, ammo(stack_size - 10)),
+            pile(&mut world, Vec3::X, ammo(15)),
+        ];
+
+        let emptied = test_utils::with_item_data(&mut world, |item_data| {
+            merge_loot_piles(&mut piles, 1.5, item_data)
+        });
+
+        assert!(emptied.is_empty());
+        assert_eq!(piles[0].item, ammo(stack_size));
+        assert_eq!(piles[1].item, ammo(5));
+    }
+
+    #[test]
+    fn caps_lowest_rarity_then_oldest_first() {
+        let mut world = test_utils::create_world();
+        world.insert_resource(GroundLootSettings {
+            max_ground_loot: 3,
+            ..default()
+        });
+
+        let mut spawn = |item, spawn_time| {
+            world
+                .spawn((GroundLoot(item), GroundLootSpawnTime(spawn_time)))
+                .id()
+        };
+
+        // rare
+        let med_kit = spawn(
+            inventory::InventoryItem::Consumable(data::ConsumableType::MedKit, 1),
+            0.0,
+        );
+        // uncommon
+        let shield_cell = spawn(
+            inventory::InventoryItem::Consumable(data::ConsumableType::ShieldCell, 1),
+            0.5,
+        );
+        // common
+        let old_ammo = spawn(ammo(10), 1.0);
+        let new_bandages = spawn(
+            inventory::InventoryItem::Consumable(data::ConsumableType::Bandage, 3),
+            2.0,
+        );
+
+        world.run_system_once(cap_ground_loot).unwrap();
+
+        assert!(world.get_entity(old_ammo).is_err());
+        assert!(world.get_entity(new_bandages).is_ok());
+        assert!(world.get_entity(shield_cell).is_ok());
+        assert!(world.get_entity(med_kit).is_ok());
+
+        world.resource_mut::<GroundLootSettings>().max_ground_loot = 1;
+        world.run_system_once(cap_ground_loot).unwrap();
+
+        assert!(world.get_entity(new_bandages).is_err());
+        assert!(world.get_entity(shield_cell).is_err());
+        assert!(world.get_entity(med_kit).is_ok());
+    }
+
+    #[test]
+    fn does_not_cap_spawned_loot() {
+        let mut world = test_utils::create_world();
+        world.insert_resource(GroundLootSettings {
+            max_ground_loot: 1,
+            ..default()
+        });
+
+        let spawned_ammo = world
+            .spawn((GroundLoot(ammo(10)), GroundLootSpawnTime(0.0), SpawnedLoot))
+            .id();
+        let old_ammo = world
+            .spawn((GroundLoot(ammo(10)), GroundLootSpawnTime(1.0)))
+            .id();
+        let new_ammo = world
+            .spawn((GroundLoot(ammo(10)), GroundLootSpawnTime(2.0)))
+            .id();
+
+        world.run_system_once(cap_ground_loot).unwrap();
+
+        assert!(world.get_entity(spawned_ammo).is_ok());
+        assert!(world.get_entity(old_ammo).is_err());
+        assert!(world.get_entity(new_ammo).is_ok());
+    }
 }
@@ -1,8 +1,11 @@
 mod assets;
 mod camera;
+mod consumable;
+mod container;
 mod cursor;
 mod data;
 mod debug;
+mod health;
 mod hud;
 mod input;
 mod interactables;
@@ -11,21 +14,86 @@ mod loot;
 mod player;
 mod projectile;
 mod spawn;
+#[cfg(test)]
+mod test_utils;
 mod ui;
 mod weapon;
 mod world;
 
+use std::collections::HashMap;
+
 use avian3d::prelude::*;
 use bevy::{
     prelude::*,
     window::{CursorGrabMode, PrimaryWindow},
 };
 use rand::prelude::*;
+use strum::IntoEnumIterator;
 
 const DEFAULT_RESOLUTION: (f32, f32) = (1280.0, 720.0);
 
-#[derive(Debug, Deref, DerefMut, Resource)]
-pub struct RandomSource(StdRng);
+// independent streams so that adding rolls to one
+// doesn't change the results of the others
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::EnumIter)]
+pub enum RandomStream {
+    Loot,
+    Spawns,
+    Spread,
+}
+
+#[derive(Debug, Resource)]
+pub struct RandomSource {
+    streams: HashMap<RandomStream, StdRng>,
+}
+
+impl RandomSource {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            streams: RandomStream::iter()
+                .map(|stream| (stream, StdRng::from_seed(stream_seed(seed, stream))))
+                .collect(),
+        }
+    }
+
+    pub fn stream(&mut self, stream: RandomStream) -> &mut StdRng {
+        self.streams.get_mut(&stream).unwrap()
+    }
+}
+
+// the seed and stream are packed side by side
+// so no two (seed, stream) pairs share a stream
+fn stream_seed(seed: u64, stream: RandomStream) -> [u8; 32] {
+    let mut stream_seed = [0; 32];
+    stream_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    stream_seed[8..16].copy_from_slice(&(stream as u64).to_le_bytes());
+    stream_seed
+}
+
+// --seed <u64>
+fn parse_seed_arg() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    let seed = args.next()?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            warn!("invalid seed {:?}: {}", seed, err);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Deref, Resource)]
+pub struct GameMode(data::GameModeType);
+
+impl GameMode {
+    pub fn get_inventory_capacity(&self, datum: &data::GameModeDatum) -> data::InventoryCapacity {
+        datum.get(&self.0).unwrap().inventory_capacity
+    }
+
+    pub fn get_loot_respawn_interval(&self, datum: &data::GameModeDatum) -> Option<f32> {
+        datum.get(&self.0).unwrap().loot_respawn_interval
+    }
+}
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, States, Reflect)]
 pub enum AppState {
@@ -44,6 +112,7 @@ pub enum GameCollisionLayers {
     Loot,
     Projectile,
     Interactable,
+    HitZone,
 }
 
 pub const WORLD_INTERACT_LAYERS: [GameCollisionLayers; 4] = [
@@ -60,12 +129,14 @@ pub const PLAYER_INTERACT_LAYERS: [GameCollisionLayers; 4] = [
 ];
 pub const LOOT_INTERACT_LAYERS: [GameCollisionLayers; 2] =
     [GameCollisionLayers::Default, GameCollisionLayers::World];
-pub const PROJECTILE_INTERACT_LAYERS: [GameCollisionLayers; 3] = [
+pub const PROJECTILE_INTERACT_LAYERS: [GameCollisionLayers; 4] = [
     GameCollisionLayers::Default,
     GameCollisionLayers::World,
     GameCollisionLayers::Player,
+    GameCollisionLayers::HitZone,
 ];
 pub const INTERACTABLE_INTERACT_LAYERS: [GameCollisionLayers; 1] = [GameCollisionLayers::Player];
+pub const HIT_ZONE_INTERACT_LAYERS: [GameCollisionLayers; 1] = [GameCollisionLayers::Projectile];
 
 pub fn show_cursor(window: &mut Window, show: bool) {
     window.cursor_options.grab_mode = if show {
@@ -78,8 +149,12 @@ pub fn show_cursor(window: &mut Window, show: bool) {
 }
 
 fn setup(mut commands: Commands) {
-    let rng = StdRng::from_rng(&mut rand::rng());
-    commands.insert_resource(RandomSource(rng));
+    let seed = parse_seed_arg().unwrap_or_else(rand::random);
+    info!("using random seed {}", seed);
+    commands.insert_resource(RandomSource::new(seed));
+
+    // TODO: game mode should come from the lobby
+    commands.insert_resource(GameMode(data::GameModeType::Standard));
 
     data::register_data(&mut commands);
 }
@@ -115,23 +190,53 @@ fn init_ui(mut commands: Commands, mut window_query: Query<&mut Window, With<Pri
     );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_loot(
     mut commands: Commands,
     game_assets: Res<assets::GameAssets>,
     weapon_datum: Res<data::WeaponDataSource>,
     ammo_datum: Res<data::AmmoDataSource>,
+    consumable_datum: Res<data::ConsumableDataSource>,
+    game_mode: Res<GameMode>,
+    game_mode_datum: Res<data::GameModeDataSource>,
     mut random: ResMut<RandomSource>,
-    loot_spawn_query: Query<&GlobalTransform, With<spawn::GroundLootSpawn>>,
+    mut loot_spawn_query: Query<(&GlobalTransform, &mut spawn::GroundLootSpawn)>,
+    mut container_query: Query<&mut container::Container>,
 ) {
-    for loot_spawn in loot_spawn_query.iter() {
-        loot::spawn_ground_loot(
-            &mut commands,
-            &game_assets,
+    let respawn_interval = game_mode.get_loot_respawn_interval(&game_mode_datum);
+    for (global_transform, mut loot_spawn) in loot_spawn_query.iter_mut() {
+        loot_spawn.set_respawn_interval(respawn_interval);
+
+        let item = inventory::InventoryItem::random_loot(
+            &mut random,
             &weapon_datum,
             &ammo_datum,
-            &mut random,
-            loot_spawn,
+            &consumable_datum,
         );
+
+        let loot = loot::spawn_ground_loot(
+            &mut commands,
+            &game_assets,
+            item,
+            global_transform.compute_transform(),
+        )
+        .insert(loot::SpawnedLoot)
+        .id();
+        loot_spawn.set_loot(loot);
+    }
+
+    for mut container in container_query.iter_mut() {
+        let count = random
+            .stream(RandomStream::Loot)
+            .random_range(container::CONTAINER_LOOT_COUNT);
+        for _ in 0..count {
+            container.add_item(inventory::InventoryItem::random_loot(
+                &mut random,
+                &weapon_datum,
+                &ammo_datum,
+                &consumable_datum,
+            ));
+        }
     }
 }
 
@@ -139,14 +244,25 @@ fn spawn_player(
     mut commands: Commands,
     mut random: ResMut<RandomSource>,
     game_assets: Res<assets::GameAssets>,
+    game_mode: Res<GameMode>,
+    game_mode_datum: Res<data::GameModeDataSource>,
+    spatial_query: SpatialQuery,
     player_spawn_query: Query<&GlobalTransform, With<spawn::PlayerSpawn>>,
 ) {
-    let mut player_spawns = player_spawn_query.iter().collect::<Vec<_>>();
-
     // TODO: this would be done for each player / party
-    let idx = (0..player_spawns.len()).choose(&mut random).unwrap();
-    let player_spawn = player_spawns.swap_remove(idx);
-    player::spawn_player(&mut commands, &game_assets, player_spawn);
+    let Some(player_spawn) =
+        spawn::choose_player_spawn(&spatial_query, &mut random, player_spawn_query.iter(), &[])
+    else {
+        spawn::queue_player_spawn(&mut commands, 0.0, true);
+        return;
+    };
+
+    player::spawn_player(
+        &mut commands,
+        &game_assets,
+        player_spawn,
+        game_mode.get_inventory_capacity(&game_mode_datum),
+    );
 }
 
 // TODO: put this in the debug plugin
@@ -225,11 +341,15 @@ fn main() {
             cursor::CursorPlugin,
             world::WorldPlugin,
             loot::GroundLootPlugin,
+            health::HealthPlugin,
             inventory::InventoryPlugin,
+            consumable::ConsumablePlugin,
+            container::ContainerPlugin,
             player::PlayerPlugin,
             weapon::WeaponPlugin,
             projectile::ProjectilePlugin,
             interactables::InteractablesPlugin,
+            spawn::SpawnPlugin,
             debug::DebugPlugin,
         ))
         // update continuously even while unfocused (for networking)
@@ -243,7 +363,8 @@ fn main() {
         .add_systems(OnEnter(AppState::LoadAssets), assets::load_assets)
         .add_systems(
             OnEnter(AppState::InGame),
-            (init_ui, spawn_loot, spawn_player),
+            // the HUD has to exist before the player so it can pick up their initial state
+            (init_ui, spawn_loot, spawn_player).chain(),
         )
         .add_systems(
             Update,
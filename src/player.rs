@@ -1,12 +1,13 @@
-use std::ops::Deref;
+use std::{collections::HashSet, ops::Deref};
 
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{color::palettes::css, prelude::*};
 use bevy_tnua::prelude::*;
 
 use crate::{
-    AppState, GameCollisionLayers, PLAYER_INTERACT_LAYERS, assets, camera, cursor, data, input,
-    interactables, inventory,
+    AppState, GameCollisionLayers, HIT_ZONE_INTERACT_LAYERS, PLAYER_INTERACT_LAYERS, assets,
+    camera, consumable, container, cursor, data, health, input, interactables, inventory, loot,
+    projectile, spawn,
 };
 
 #[derive(Debug, Resource)]
@@ -42,14 +43,45 @@ impl Player {
 #[derive(Debug, Component)]
 pub struct LocalPlayer;
 
+// a throwable with the pin pulled
+#[derive(Debug, Component)]
+pub struct CookingThrowable {
+    pub throwable_type: data::ThrowableType,
+    fuse: Timer,
+}
+
+impl CookingThrowable {
+    pub fn get_fuse_remaining(&self) -> f32 {
+        self.fuse.remaining_secs()
+    }
+}
+
 #[derive(Debug, Component)]
 pub struct PlayerModel;
 
 // TODO: move to player data
 const MODEL_PATH: &str = "human_1.glb";
 const MOVE_SPEED: f32 = 8.0;
+const DOWNED_MOVE_SPEED: f32 = 1.5;
 const HEIGHT: f32 = 2.0;
 const MASS: f32 = 75.0;
+// tnua floats the body center this far off the ground
+const FLOAT_HEIGHT: f32 = HEIGHT * 0.75;
+// shots travel level at HAND_HEIGHT so the head has to sit on that line
+// and the body has to stay below it, otherwise the body swallows every hit
+const HEAD_OFFSET: f32 = HAND_HEIGHT - FLOAT_HEIGHT;
+const HEAD_RADIUS: f32 = 0.3;
+const BODY_RADIUS: f32 = HEIGHT * 0.25;
+const BODY_LENGTH: f32 = HEIGHT * 0.25;
+const BODY_OFFSET: f32 = HEAD_OFFSET - HEAD_RADIUS - BODY_RADIUS - BODY_LENGTH * 0.5;
+const MAX_HEALTH: usize = 100;
+const SHIELD_CAPACITY: usize = 50;
+const SHIELD_REGEN_DELAY: f32 = 3.0;
+const SHIELD_REGEN_RATE: f32 = 10.0;
+const BLEED_OUT_TIME: f32 = 30.0;
+const REVIVE_HEALTH: usize = 30;
+const HAND_HEIGHT: f32 = 1.5;
+const THROW_ARC_SEGMENTS: usize = 32;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
 pub struct PlayerSet;
@@ -61,7 +93,18 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (move_player, (handle_weapon_select_input, handle_firing))
+            (
+                move_player,
+                (
+                    handle_weapon_select_input,
+                    handle_firing,
+                    handle_throwing,
+                    handle_cooking_input,
+                    handle_quick_heal_input,
+                    handle_use_item_input,
+                    handle_inventory_input,
+                ),
+            )
                 .chain()
                 .after(input::InputSet)
                 .run_if(in_state(AppState::InGame))
@@ -69,20 +112,28 @@ impl Plugin for PlayerPlugin {
         )
         .add_systems(
             Update,
-            update_player
+            (
+                update_player,
+                update_hold_interaction,
+                update_cooking,
+                draw_throw_arc,
+            )
+                .after(input::InputSet)
                 .run_if(in_state(AppState::InGame))
                 .in_set(PlayerSet),
         )
-        .add_systems(PostProcessCollisions, handle_interact_input);
+        .add_systems(
+            PostProcessCollisions,
+            (handle_interact_input, handle_auto_pickup),
+        );
     }
 }
 
 fn update_player(
     time: Res<Time>,
-    mut inventory: ResMut<inventory::Inventory>,
-    mut player_query: Query<&mut Player>,
+    mut player_query: Query<(&mut Player, &mut inventory::Inventory)>,
 ) {
-    for mut player in player_query.iter_mut() {
+    for (mut player, mut inventory) in player_query.iter_mut() {
         player.toggle_select_timer.tick(time.delta());
         if player.toggle_select_timer.just_finished() {
             info!("toggle weapon");
@@ -99,11 +150,27 @@ fn update_player(
 
 fn move_player(
     input_state: Res<input::InputState>,
-    mut player_query: Query<(&mut TnuaController, &GlobalTransform), With<LocalPlayer>>,
+    mut player_query: Query<
+        (
+            &mut TnuaController,
+            &GlobalTransform,
+            Option<&consumable::SpeedBoost>,
+            Has<health::Downed>,
+            Has<consumable::Channeling>,
+        ),
+        With<LocalPlayer>,
+    >,
     cursor_query: Query<&Node, With<cursor::Cursor>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<camera::MainCamera>>,
 ) {
-    if let Ok((mut character_controller, player_global_transform)) = player_query.get_single_mut() {
+    if let Ok((
+        mut character_controller,
+        player_global_transform,
+        speed_boost,
+        downed,
+        channeling,
+    )) = player_query.get_single_mut()
+    {
         let cursor_node = cursor_query.single();
         let (camera, camera_global_transform) = camera_query.single();
 
@@ -119,11 +186,26 @@ fn move_player(
 
         let player_global_position = player_global_transform.translation();
 
+        // downed players can only crawl
+        let mut move_speed = if downed {
+            DOWNED_MOVE_SPEED
+        } else {
+            MOVE_SPEED
+        };
+
+        if channeling {
+            move_speed *= consumable::CHANNEL_MOVE_SPEED_MODIFIER;
+        }
+
+        if let Some(speed_boost) = speed_boost {
+            move_speed *= speed_boost.get_multiplier();
+        }
+
         character_controller.basis(TnuaBuiltinWalk {
-            desired_velocity: move_direction.normalize_or_zero() * MOVE_SPEED,
+            desired_velocity: move_direction.normalize_or_zero() * move_speed,
             desired_forward: Dir3::new(look_at - player_global_position).ok(),
             // TODO: this doesn't seem right by the docs / examples?
-            float_height: HEIGHT * 0.75,
+            float_height: FLOAT_HEIGHT,
             ..Default::default()
         });
     }
@@ -132,26 +214,58 @@ fn move_player(
 fn handle_interact_input(
     mut commands: Commands,
     mut evr_interact: EventReader<input::InteractInputEvent>,
-    player_query: Query<&CollidingEntities, With<LocalPlayer>>,
+    player_query: Query<
+        (Entity, &CollidingEntities, Has<container::Searching>),
+        (
+            With<LocalPlayer>,
+            Without<health::Downed>,
+            Without<interactables::HoldInteraction>,
+        ),
+    >,
     interactable_query: Query<(&interactables::InteractableType, &Parent)>,
 ) {
     if evr_interact.is_empty() {
         return;
     }
 
-    let colliding_entities = player_query.single();
+    let Ok((player, colliding_entities, searching)) = player_query.get_single() else {
+        evr_interact.clear();
+        return;
+    };
 
-    for entity in colliding_entities.iter() {
-        let interactable = interactable_query
-            .get(*entity)
-            .or_else(|_| interactable_query.get(*entity));
+    // interacting again closes the open container
+    if searching {
+        info!("close container");
+        commands.entity(player).remove::<container::Searching>();
+        evr_interact.clear();
+        return;
+    }
 
-        if let Ok((interactable, parent)) = interactable {
+    for entity in colliding_entities.iter() {
+        if let Ok((interactable, parent)) = interactable_query.get(*entity) {
             let parent = parent.get();
-            commands.trigger(interactables::InteractEvent {
-                target: parent,
-                target_type: *interactable,
-            });
+
+            // we can't interact with things attached to ourself
+            if parent == player {
+                continue;
+            }
+
+            if let Some(duration) = interactable.get_hold_duration() {
+                info!("start hold interaction {} with {}", interactable, parent);
+                commands
+                    .entity(player)
+                    .insert(interactables::HoldInteraction::new(
+                        parent,
+                        *interactable,
+                        duration,
+                    ));
+            } else {
+                commands.trigger(interactables::InteractEvent {
+                    player,
+                    target: parent,
+                    target_type: *interactable,
+                });
+            }
             break;
         }
     }
@@ -159,15 +273,136 @@ fn handle_interact_input(
     evr_interact.clear();
 }
 
+fn handle_auto_pickup(
+    mut commands: Commands,
+    settings: Res<loot::AutoPickupSettings>,
+    weapon_datum: Res<data::WeaponDataSource>,
+    item_data: inventory::ItemData,
+    mut player_query: Query<
+        (&CollidingEntities, &mut inventory::Inventory),
+        (With<Player>, Without<health::Downed>),
+    >,
+    interactable_query: Query<(&interactables::InteractableType, &Parent)>,
+    // loot that's still being tossed was just dropped
+    // so leave it alone instead of picking it straight back up
+    mut ground_loot_query: Query<&mut loot::GroundLoot, Without<loot::Settling>>,
+) {
+    // the despawn is deferred so anyone else touching
+    // the same loot this pass must skip it
+    let mut picked_up = HashSet::new();
+
+    for (colliding_entities, mut inventory) in player_query.iter_mut() {
+        for entity in colliding_entities.iter() {
+            let Ok((interactable, parent)) = interactable_query.get(*entity) else {
+                continue;
+            };
+
+            if *interactable != interactables::InteractableType::GroundLoot {
+                continue;
+            }
+
+            if picked_up.contains(&parent.get()) {
+                continue;
+            }
+
+            let Ok(mut loot) = ground_loot_query.get_mut(parent.get()) else {
+                continue;
+            };
+
+            let item = *loot.deref();
+            if !settings.is_enabled(item.get_category()) {
+                continue;
+            }
+
+            if let inventory::InventoryItem::Ammo(ammo_type, _) = item {
+                if !inventory.uses_ammo(ammo_type, &weapon_datum) {
+                    continue;
+                }
+            }
+
+            match inventory.add_item(item, &item_data) {
+                Ok(()) => {
+                    info!("auto picked up ground loot {:?}", item);
+                    picked_up.insert(parent.get());
+                    commands.entity(parent.get()).despawn_recursive();
+                }
+                // partial pickups leave the remainder on the ground
+                Err(rejection) => {
+                    if rejection.remaining != item {
+                        info!(
+                            "auto picked up part of ground loot {:?}, leaving {:?}",
+                            item, rejection.remaining
+                        );
+                        **loot = rejection.remaining;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn update_hold_interaction(
+    mut commands: Commands,
+    time: Res<Time>,
+    input_state: Res<input::InputState>,
+    mut player_query: Query<
+        (
+            Entity,
+            &CollidingEntities,
+            &mut interactables::HoldInteraction,
+            Has<health::Downed>,
+        ),
+        With<LocalPlayer>,
+    >,
+    interactable_query: Query<(&interactables::InteractableType, &Parent)>,
+) {
+    let Ok((player, colliding_entities, mut hold_interaction, downed)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let in_range = colliding_entities.iter().any(|entity| {
+        interactable_query
+            .get(*entity)
+            .is_ok_and(|(interactable, parent)| {
+                parent.get() == hold_interaction.target
+                    && *interactable == hold_interaction.target_type
+            })
+    });
+
+    if !input_state.interacting || downed || !in_range {
+        info!("cancel hold interaction {}", hold_interaction.target_type);
+        commands
+            .entity(player)
+            .remove::<interactables::HoldInteraction>();
+        return;
+    }
+
+    if hold_interaction.tick(&time) {
+        commands.trigger(interactables::InteractEvent {
+            player,
+            target: hold_interaction.target,
+            target_type: hold_interaction.target_type,
+        });
+        commands
+            .entity(player)
+            .remove::<interactables::HoldInteraction>();
+    }
+}
+
 fn handle_weapon_select_input(
-    inventory: Res<inventory::Inventory>,
     mut evr_toggle_weapon: EventReader<input::ToggleWeaponInputEvent>,
     mut evr_select_weapon: EventReader<input::SelectWeaponInputEvent>,
-    mut player_query: Query<&mut Player, With<LocalPlayer>>,
+    mut player_query: Query<(&mut Player, &inventory::Inventory), With<LocalPlayer>>,
 ) {
     // TODO: we can't select / toggle empty weapon slots
-    if inventory.has_weapon() {
-        let mut player = player_query.single_mut();
+    if let Some(mut player) = player_query
+        .get_single_mut()
+        .ok()
+        .filter(|(_, inventory)| inventory.has_weapon() || inventory.get_throwable().is_some())
+        .map(|(player, _)| player)
+    {
         if (!player.toggle_select_timer.paused() && !player.toggle_select_timer.finished())
             || (!player.weapon_select_timer.1.paused() && !player.weapon_select_timer.1.finished())
         {
@@ -199,27 +434,41 @@ fn handle_weapon_select_input(
 fn handle_firing(
     mut commands: Commands,
     input_state: Res<input::InputState>,
-    mut inventory: ResMut<inventory::Inventory>,
     datum: Res<data::WeaponDataSource>,
     time: Res<Time>,
-    player_query: Query<(Entity, &GlobalTransform), With<LocalPlayer>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut inventory::Inventory,
+            Has<spawn::SpawnProtection>,
+        ),
+        (With<LocalPlayer>, Without<health::Downed>),
+    >,
 ) {
     if !input_state.firing {
         return;
     }
 
-    let weapon = inventory.get_selected_weapon_item_mut();
-    if let Some(weapon) = weapon {
-        let (entity, global_transform) = player_query.single();
+    let Ok((entity, global_transform, mut inventory, spawn_protected)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
 
+    if inventory.get_selected_weapon_item().is_some() {
         let mut origin = global_transform.compute_transform();
-        origin.translation.y = 1.5;
+        origin.translation.y = HAND_HEIGHT;
         // TODO: we might want to spawn this in front of the player as well
         // (currently it spawns inside the player and we filter the collision)
 
         // TODO: if we can do this with an event / trigger
         // it might be cleaner than calling a function
-        if !weapon.fire(&mut commands, entity, &datum, &time, &origin) {
+        if inventory.fire_selected_weapon(&mut commands, entity, &datum, &time, &origin) {
+            if spawn_protected {
+                spawn::remove_spawn_protection(&mut commands, entity);
+            }
+        } else {
             // TODO: if we have a different weapon and it can fire, switch to it (this takes time)
             // otherwise if we have the correct type of ammo in our inventory
             // trigger a reload (this takes time)
@@ -227,6 +476,460 @@ fn handle_firing(
     }
 }
 
+fn handle_quick_heal_input(
+    mut commands: Commands,
+    mut evr_quick_heal: EventReader<input::QuickHealInputEvent>,
+    datum: Res<data::ConsumableDataSource>,
+    player_query: Query<
+        (
+            Entity,
+            &inventory::Inventory,
+            &health::Health,
+            Option<&health::Shield>,
+        ),
+        (With<LocalPlayer>, Without<container::Searching>),
+    >,
+) {
+    if evr_quick_heal.is_empty() {
+        return;
+    }
+    evr_quick_heal.clear();
+
+    let Ok((entity, inventory, health, shield)) = player_query.get_single() else {
+        return;
+    };
+
+    let Some(consumable_type) = consumable::choose_quick_heal(inventory, &datum, health, shield)
+    else {
+        info!("nothing to quick heal with");
+        return;
+    };
+
+    commands.trigger_targets(consumable::UseConsumableEvent { consumable_type }, entity);
+}
+
+fn handle_use_item_input(
+    mut commands: Commands,
+    mut evr_use_item: EventReader<input::UseItemInputEvent>,
+    player_query: Query<Entity, (With<LocalPlayer>, Without<container::Searching>)>,
+) {
+    let Ok(entity) = player_query.get_single() else {
+        evr_use_item.clear();
+        return;
+    };
+
+    for evt in evr_use_item.read() {
+        commands.trigger_targets(inventory::UseItemEvent { slot: **evt }, entity);
+    }
+}
+
+fn handle_inventory_input(
+    mut commands: Commands,
+    mut evr_drop_item: EventReader<input::DropItemInputEvent>,
+    mut evr_drop_weapon: EventReader<input::DropWeaponInputEvent>,
+    mut evr_toggle_sort_mode: EventReader<input::ToggleSortModeInputEvent>,
+    mut evr_move_item: EventReader<input::MoveItemInputEvent>,
+    mut evr_split_stack: EventReader<input::SplitStackInputEvent>,
+    player_query: Query<
+        (Entity, &inventory::Inventory),
+        (With<LocalPlayer>, Without<container::Searching>),
+    >,
+) {
+    let Ok((entity, inventory)) = player_query.get_single() else {
+        evr_drop_item.clear();
+        evr_drop_weapon.clear();
+        evr_toggle_sort_mode.clear();
+        evr_move_item.clear();
+        evr_split_stack.clear();
+        return;
+    };
+
+    for evt in evr_drop_item.read() {
+        commands.trigger_targets(
+            inventory::DropItemEvent {
+                slot: **evt,
+                count: None,
+            },
+            entity,
+        );
+    }
+
+    for _ in evr_drop_weapon.read() {
+        commands.trigger_targets(inventory::DropWeaponEvent, entity);
+    }
+
+    for _ in evr_toggle_sort_mode.read() {
+        let sort_mode = match inventory.get_sort_mode() {
+            inventory::SortMode::Automatic => inventory::SortMode::Manual,
+            inventory::SortMode::Manual => inventory::SortMode::Automatic,
+        };
+        commands.trigger_targets(inventory::SetSortModeEvent { sort_mode }, entity);
+    }
+
+    for evt in evr_move_item.read() {
+        let slot = **evt;
+        if slot == 0 {
+            continue;
+        }
+
+        commands.trigger_targets(
+            inventory::SwapSlotsEvent {
+                a: slot,
+                b: slot - 1,
+            },
+            entity,
+        );
+    }
+
+    for evt in evr_split_stack.read() {
+        let slot = **evt;
+        let Some(item) = inventory.get_items().get(slot) else {
+            continue;
+        };
+
+        commands.trigger_targets(
+            inventory::SplitStackEvent {
+                slot,
+                count: item.get_count() / 2,
+            },
+            entity,
+        );
+    }
+}
+
+fn get_hand_position(player_global_transform: &GlobalTransform) -> Vec3 {
+    let mut position = player_global_transform.translation();
+    position.y = HAND_HEIGHT;
+    position
+}
+
+// returns the throw origin, launch velocity, gravity and time of flight
+// for throwing the given throwable at the cursor
+#[allow(clippy::too_many_arguments)]
+fn solve_throw(
+    throwable_type: data::ThrowableType,
+    throwable_datum: &data::ThrowableDatum,
+    projectile_datum: &data::ProjectileDatum,
+    gravity: &Gravity,
+    player_global_transform: &GlobalTransform,
+    cursor_node: &Node,
+    camera: &Camera,
+    camera_global_transform: &GlobalTransform,
+) -> Option<(Vec3, Vec3, Vec3, f32)> {
+    let throwable_data = throwable_datum.get(&throwable_type).unwrap();
+    let projectile_data = projectile_datum
+        .get(&throwable_data.projectile_type)
+        .unwrap();
+
+    let target = cursor::get_cursor_world_position(
+        cursor_node,
+        camera,
+        camera_global_transform,
+        player_global_transform,
+    )?;
+
+    let origin = get_hand_position(player_global_transform);
+
+    let gravity = gravity.0 * projectile_data.gravity_scale;
+    let (velocity, flight_time) = projectile::solve_ballistic_velocity(
+        origin,
+        target,
+        throwable_data.throw_angle,
+        throwable_data.max_range,
+        gravity.length(),
+    )?;
+
+    Some((origin, velocity, gravity, flight_time))
+}
+
+fn spawn_throwable<'a>(
+    commands: &'a mut Commands,
+    game_assets: &assets::GameAssets,
+    projectile_datum: &data::ProjectileDatum,
+    throwable_data: &data::ThrowableData,
+    owner: Entity,
+    origin: Vec3,
+    velocity: Vec3,
+) -> EntityCommands<'a> {
+    info!("throwing {}", throwable_data.name);
+
+    // zero velocity throws are dropped at our feet
+    let direction = Dir3::new(velocity).unwrap_or(Dir3::NEG_Y);
+
+    projectile::spawn_projectile(
+        commands,
+        game_assets,
+        projectile_datum,
+        throwable_data.projectile_type,
+        owner,
+        origin,
+        direction,
+        velocity.length(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_throwing(
+    mut commands: Commands,
+    mut evr_throwing_end: EventReader<input::ThrowingInputEndEvent>,
+    game_assets: Res<assets::GameAssets>,
+    throwable_datum: Res<data::ThrowableDataSource>,
+    projectile_datum: Res<data::ProjectileDataSource>,
+    gravity: Res<Gravity>,
+    mut player_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut inventory::Inventory,
+            Has<spawn::SpawnProtection>,
+        ),
+        (With<LocalPlayer>, Without<health::Downed>),
+    >,
+    cursor_query: Query<&Node, With<cursor::Cursor>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::MainCamera>>,
+) {
+    if evr_throwing_end.is_empty() {
+        return;
+    }
+    evr_throwing_end.clear();
+
+    let Ok((entity, player_global_transform, mut inventory, spawn_protected)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Some(throwable_type) = inventory.get_throwable() else {
+        return;
+    };
+    let cursor_node = cursor_query.single();
+    let (camera, camera_global_transform) = camera_query.single();
+
+    let Some((origin, velocity, _, _)) = solve_throw(
+        throwable_type,
+        &throwable_datum,
+        &projectile_datum,
+        &gravity,
+        player_global_transform,
+        cursor_node,
+        camera,
+        camera_global_transform,
+    ) else {
+        return;
+    };
+
+    inventory.take_throwable();
+
+    if spawn_protected {
+        spawn::remove_spawn_protection(&mut commands, entity);
+    }
+
+    spawn_throwable(
+        &mut commands,
+        &game_assets,
+        &projectile_datum,
+        throwable_datum.get(&throwable_type).unwrap(),
+        entity,
+        origin,
+        velocity,
+    );
+}
+
+// hold fire with the throwable slot selected to pull the pin and cook
+// release to throw with whatever is left of the fuse
+#[allow(clippy::too_many_arguments)]
+fn handle_cooking_input(
+    mut commands: Commands,
+    mut evr_firing_start: EventReader<input::FiringInputStartEvent>,
+    mut evr_firing_end: EventReader<input::FiringInputEndEvent>,
+    game_assets: Res<assets::GameAssets>,
+    throwable_datum: Res<data::ThrowableDataSource>,
+    projectile_datum: Res<data::ProjectileDataSource>,
+    gravity: Res<Gravity>,
+    mut player_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut inventory::Inventory,
+            Option<&CookingThrowable>,
+            Has<spawn::SpawnProtection>,
+            Has<health::Downed>,
+        ),
+        With<LocalPlayer>,
+    >,
+    cursor_query: Query<&Node, With<cursor::Cursor>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::MainCamera>>,
+) {
+    let started = !evr_firing_start.is_empty();
+    let ended = !evr_firing_end.is_empty();
+    evr_firing_start.clear();
+    evr_firing_end.clear();
+
+    let Ok((entity, player_global_transform, mut inventory, cooking, spawn_protected, downed)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    match cooking {
+        None if started && !downed => {
+            if inventory.get_selected_weapon() != inventory::WeaponSlot::Throwable {
+                return;
+            }
+
+            let Some(throwable_type) = inventory.get_throwable() else {
+                return;
+            };
+
+            let throwable_data = throwable_datum.get(&throwable_type).unwrap();
+            let Some(fuse) = projectile_datum
+                .get(&throwable_data.projectile_type)
+                .unwrap()
+                .fuse
+            else {
+                warn!("{} can't be cooked", throwable_data.name);
+                return;
+            };
+
+            info!("cooking {}", throwable_data.name);
+
+            inventory.take_throwable();
+            commands.entity(entity).insert(CookingThrowable {
+                throwable_type,
+                fuse: Timer::from_seconds(fuse, TimerMode::Once),
+            });
+        }
+        Some(cooking) if ended => {
+            let cursor_node = cursor_query.single();
+            let (camera, camera_global_transform) = camera_query.single();
+
+            // downed players (and throws we can't solve) just drop it
+            let (origin, velocity) = solve_throw(
+                cooking.throwable_type,
+                &throwable_datum,
+                &projectile_datum,
+                &gravity,
+                player_global_transform,
+                cursor_node,
+                camera,
+                camera_global_transform,
+            )
+            .filter(|_| !downed)
+            .map(|(origin, velocity, _, _)| (origin, velocity))
+            .unwrap_or_else(|| (get_hand_position(player_global_transform), Vec3::ZERO));
+
+            if spawn_protected {
+                spawn::remove_spawn_protection(&mut commands, entity);
+            }
+
+            spawn_throwable(
+                &mut commands,
+                &game_assets,
+                &projectile_datum,
+                throwable_datum.get(&cooking.throwable_type).unwrap(),
+                entity,
+                origin,
+                velocity,
+            )
+            .insert(projectile::Fuse::new(cooking.fuse.remaining_secs()));
+
+            commands.entity(entity).remove::<CookingThrowable>();
+        }
+        _ => (),
+    }
+}
+
+fn update_cooking(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_assets: Res<assets::GameAssets>,
+    throwable_datum: Res<data::ThrowableDataSource>,
+    projectile_datum: Res<data::ProjectileDataSource>,
+    mut cooking_query: Query<(Entity, &GlobalTransform, &mut CookingThrowable)>,
+) {
+    for (entity, global_transform, mut cooking) in cooking_query.iter_mut() {
+        cooking.fuse.tick(time.delta());
+        if !cooking.fuse.just_finished() {
+            continue;
+        }
+
+        let throwable_data = throwable_datum.get(&cooking.throwable_type).unwrap();
+        warn!("{} cooked {} too long", entity, throwable_data.name);
+
+        commands.entity(entity).remove::<CookingThrowable>();
+
+        // an expired fuse detonates as soon as it ticks
+        projectile::spawn_projectile(
+            &mut commands,
+            &game_assets,
+            &projectile_datum,
+            throwable_data.projectile_type,
+            entity,
+            get_hand_position(global_transform),
+            Dir3::NEG_Y,
+            0.0,
+        )
+        .insert(projectile::Fuse::new(0.0));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_throw_arc(
+    mut gizmos: Gizmos,
+    input_state: Res<input::InputState>,
+    throwable_datum: Res<data::ThrowableDataSource>,
+    projectile_datum: Res<data::ProjectileDataSource>,
+    gravity: Res<Gravity>,
+    player_query: Query<
+        (
+            &GlobalTransform,
+            &inventory::Inventory,
+            Option<&CookingThrowable>,
+        ),
+        (With<LocalPlayer>, Without<health::Downed>),
+    >,
+    cursor_query: Query<&Node, With<cursor::Cursor>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::MainCamera>>,
+) {
+    let Ok((player_global_transform, inventory, cooking)) = player_query.get_single() else {
+        return;
+    };
+
+    let throwable_type = match cooking {
+        Some(cooking) => cooking.throwable_type,
+        None if input_state.throwing => {
+            let Some(throwable_type) = inventory.get_throwable() else {
+                return;
+            };
+            throwable_type
+        }
+        None => return,
+    };
+    let cursor_node = cursor_query.single();
+    let (camera, camera_global_transform) = camera_query.single();
+
+    let Some((origin, velocity, gravity, flight_time)) = solve_throw(
+        throwable_type,
+        &throwable_datum,
+        &projectile_datum,
+        &gravity,
+        player_global_transform,
+        cursor_node,
+        camera,
+        camera_global_transform,
+    ) else {
+        return;
+    };
+
+    gizmos.linestrip(
+        (0..=THROW_ARC_SEGMENTS).map(|idx| {
+            let time = flight_time * idx as f32 / THROW_ARC_SEGMENTS as f32;
+            projectile::ballistic_position(origin, velocity, gravity, time)
+        }),
+        css::YELLOW,
+    );
+}
+
 pub fn load_player_assets(
     commands: &mut Commands,
     asset_server: &AssetServer,
@@ -245,20 +948,29 @@ pub fn load_player_assets(
     asset_server.load(GltfAssetLabel::Scene(0).from_asset(MODEL_PATH))
 }
 
-pub fn spawn_player(
-    commands: &mut Commands,
+pub fn spawn_player<'a>(
+    commands: &'a mut Commands,
     game_assets: &assets::GameAssets,
     spawn_transform: &GlobalTransform,
-) {
+    inventory_capacity: data::InventoryCapacity,
+) -> EntityCommands<'a> {
     let mut commands = commands.spawn((
         spawn_transform.compute_transform(),
         Visibility::default(),
         CollidingEntities::default(),
-        Name::new("Player"),
         Player::new(),
+        inventory::Inventory::new(inventory_capacity),
+        health::Health::new(MAX_HEALTH),
+        health::Shield::new(SHIELD_CAPACITY, SHIELD_REGEN_DELAY, SHIELD_REGEN_RATE),
+        health::Armor::default(),
+        health::Downable::new(BLEED_OUT_TIME, REVIVE_HEALTH),
         LocalPlayer,
     ));
 
+    // TODO: use the player's display name once there is one
+    let name = format!("Player {}", commands.id());
+    commands.insert(Name::new(name));
+
     commands.insert((
         RigidBody::Dynamic,
         // TODO: why is the radius so small?
@@ -283,5 +995,104 @@ pub fn spawn_player(
             Name::new("Model"),
             PlayerModel,
         ));
+
+        // TODO: these should follow the model skeleton
+        spawn_hit_box(
+            parent,
+            data::HitZone::Head,
+            Vec3::new(0.0, HEAD_OFFSET, 0.0),
+            Collider::sphere(HEAD_RADIUS),
+        );
+        spawn_hit_box(
+            parent,
+            data::HitZone::Body,
+            Vec3::new(0.0, BODY_OFFSET, 0.0),
+            Collider::capsule(BODY_RADIUS, BODY_LENGTH),
+        );
     });
+
+    commands
+}
+
+fn spawn_hit_box(
+    parent: &mut ChildBuilder,
+    hit_zone: data::HitZone,
+    offset: Vec3,
+    collider: Collider,
+) {
+    parent.spawn((
+        Transform::from_translation(offset),
+        collider,
+        ColliderDensity(0.0),
+        CollisionLayers::new(GameCollisionLayers::HitZone, HIT_ZONE_INTERACT_LAYERS),
+        Sensor,
+        Name::new(format!("Hit Box - {}", hit_zone)),
+        health::HitBox(hit_zone),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::test_utils;
+
+    const AMMO: inventory::InventoryItem =
+        inventory::InventoryItem::Ammo(data::AmmoType::Light, 10);
+
+    #[test]
+    fn dropped_ammo_is_not_picked_up_until_settled() {
+        let mut world = test_utils::create_world();
+        world.init_resource::<loot::AutoPickupSettings>();
+
+        let loot = world
+            .spawn((loot::GroundLoot::new(AMMO), loot::Settling::default()))
+            .id();
+        let sensor = world
+            .spawn(interactables::InteractableType::GroundLoot)
+            .set_parent(loot)
+            .id();
+
+        // the pistol fires light ammo so it's eligible for auto pickup
+        let mut inventory = inventory::Inventory::new(data::InventoryCapacity::Slots(4));
+        test_utils::with_item_data(&mut world, |item_data| {
+            inventory
+                .add_item(
+                    inventory::InventoryItem::Weapon(data::WeaponType::Pistol, 0),
+                    item_data,
+                )
+                .unwrap();
+        });
+        let player = world
+            .spawn((
+                Player::new(),
+                inventory,
+                CollidingEntities(std::iter::once(sensor).collect()),
+            ))
+            .id();
+
+        world.run_system_once(handle_auto_pickup).unwrap();
+
+        assert_eq!(**world.get::<loot::GroundLoot>(loot).unwrap(), AMMO);
+        assert!(
+            world
+                .get::<inventory::Inventory>(player)
+                .unwrap()
+                .get_items()
+                .is_empty()
+        );
+
+        world.entity_mut(loot).remove::<loot::Settling>();
+        world.run_system_once(handle_auto_pickup).unwrap();
+
+        assert!(world.get_entity(loot).is_err());
+        assert_eq!(
+            world
+                .get::<inventory::Inventory>(player)
+                .unwrap()
+                .get_items(),
+            &vec![AMMO]
+        );
+    }
 }
@@ -1,113 +1,335 @@
-use std::borrow::Cow;
-
-use avian3d::prelude::*;
+use avian3d::{
+    collision::broad_phase::{BroadCollisionPairs, BroadPhaseSet},
+    prelude::*,
+    schedule::PhysicsSchedule,
+};
 use bevy::{color::palettes::css, prelude::*};
 
-use crate::{GameCollisionLayers, PROJECTILE_INTERACT_LAYERS, assets};
+use crate::{GameCollisionLayers, PROJECTILE_INTERACT_LAYERS, assets, data, health};
 
 #[derive(Debug, Component)]
 #[require(Transform)]
 pub struct Projectile {
     owner: Entity,
+    r#type: data::ProjectileType,
+
+    // the projectile can't collide with its owner until this finishes
+    owner_grace_timer: Timer,
 }
 
 impl Projectile {
-    fn new(owner: Entity) -> Self {
-        Self { owner }
+    fn new(owner: Entity, r#type: data::ProjectileType) -> Self {
+        Self {
+            owner,
+            r#type,
+            owner_grace_timer: Timer::from_seconds(OWNER_GRACE_PERIOD, TimerMode::Once),
+        }
+    }
+
+    fn ignores(&self, entity: Entity) -> bool {
+        entity == self.owner && !self.owner_grace_timer.finished()
     }
 }
 
 #[derive(Debug, Component)]
 pub struct ProjectileModel;
 
+#[derive(Debug, Deref, DerefMut, Component)]
+pub struct Fuse(Timer);
+
+impl Fuse {
+    pub fn new(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
 #[derive(Debug, Event)]
 pub struct ProjectileCollisionEvent {
+    pub owner: Entity,
     pub target: Entity,
+    pub hit_zone: Option<data::HitZone>,
+}
+
+#[derive(Debug, Event)]
+struct DetonateEvent;
+
+#[derive(Debug, Event)]
+pub struct ExplosionEvent {
+    pub owner: Entity,
+    pub origin: Vec3,
+    pub radius: f32,
 }
 
-const BULLET_RADIUS: f32 = 0.1;
-const MASS: f32 = 0.005;
+const OWNER_GRACE_PERIOD: f32 = 0.5;
+
+const EXPLOSION_TARGET_LAYERS: [GameCollisionLayers; 2] =
+    [GameCollisionLayers::Default, GameCollisionLayers::Player];
+const EXPLOSION_BLOCKING_LAYERS: [GameCollisionLayers; 1] = [GameCollisionLayers::World];
 
 #[derive(Debug)]
 pub struct ProjectilePlugin;
 
 impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PostProcessCollisions,
-            (filter_collisions, handle_collisions).chain(),
-        );
+        app.add_systems(Update, (update_projectiles, update_fuses))
+            .add_systems(
+                PhysicsSchedule,
+                filter_broad_collisions.in_set(BroadPhaseSet::Last),
+            )
+            .add_systems(PostProcessCollisions, handle_collisions)
+            .add_observer(on_detonate);
     }
 }
 
-// TODO: if we stop spawning projectiles inside players
-// we might be able to remove this
-fn filter_collisions(
-    mut collisions: ResMut<Collisions>,
-    projectile_query: Query<(Entity, &Projectile)>,
+fn update_projectiles(time: Res<Time>, mut projectile_query: Query<&mut Projectile>) {
+    for mut projectile in projectile_query.iter_mut() {
+        projectile.owner_grace_timer.tick(time.delta());
+    }
+}
+
+// projectiles spawn inside their owner so drop those pairs
+// before the narrow phase ever generates contacts for them
+fn filter_broad_collisions(
+    mut broad_collision_pairs: ResMut<BroadCollisionPairs>,
+    projectile_query: Query<&Projectile>,
+    collider_parent_query: Query<&ColliderParent>,
 ) {
-    for (entity, projectile) in projectile_query.iter() {
-        collisions.remove_collision_pair(entity, projectile.owner);
+    if projectile_query.is_empty() {
+        return;
     }
+
+    let ignores = |projectile: Entity, collider: Entity| {
+        let Ok(projectile) = projectile_query.get(projectile) else {
+            return false;
+        };
+
+        let body = collider_parent_query
+            .get(collider)
+            .map_or(collider, |collider_parent| collider_parent.get());
+        projectile.ignores(body)
+    };
+
+    broad_collision_pairs
+        .retain(|&(entity1, entity2)| !ignores(entity1, entity2) && !ignores(entity2, entity1));
 }
 
 fn handle_collisions(
     mut commands: Commands,
-    projectile_query: Query<(Entity, &CollidingEntities), With<Projectile>>,
+    datum: Res<data::ProjectileDataSource>,
+    projectile_query: Query<(Entity, &Projectile, &CollidingEntities)>,
+    hit_box_query: Query<(&health::HitBox, &Parent)>,
 ) {
-    for (entity, colliding_entities) in projectile_query.iter() {
+    for (entity, projectile, colliding_entities) in projectile_query.iter() {
+        let data = datum.get(&projectile.r#type).unwrap();
+
+        // ballistic projectiles bounce around until their fuse runs out
+        if data.ballistic.is_some() {
+            continue;
+        }
+
+        // a projectile can overlap a body and its hit boxes in the same frame
+        // so only report the most severe hit on each target
+        let mut hits: Vec<(Entity, Option<data::HitZone>)> = vec![];
         for colliding_entity in colliding_entities.iter() {
             debug!("projectile {} collides with {}", entity, colliding_entity);
+
+            let (target, hit_zone) = match hit_box_query.get(*colliding_entity) {
+                Ok((hit_box, parent)) => (parent.get(), Some(**hit_box)),
+                Err(_) => (*colliding_entity, None),
+            };
+
+            match hits.iter_mut().find(|(hit, _)| *hit == target) {
+                Some((_, hit)) => *hit = (*hit).max(hit_zone),
+                None => hits.push((target, hit_zone)),
+            }
+        }
+
+        if hits.is_empty() {
+            continue;
+        }
+
+        for (target, hit_zone) in hits {
             commands.trigger_targets(
                 ProjectileCollisionEvent {
-                    target: *colliding_entity,
+                    owner: projectile.owner,
+                    target,
+                    hit_zone,
                 },
                 entity,
             );
+        }
+
+        if data.explosion.is_some() {
+            commands.trigger_targets(DetonateEvent, entity);
+        } else {
             commands.entity(entity).despawn_recursive();
         }
     }
 }
 
-pub fn load_bullet_assets(
+fn update_fuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fuse_query: Query<(Entity, &mut Fuse)>,
+) {
+    for (entity, mut fuse) in fuse_query.iter_mut() {
+        fuse.tick(time.delta());
+        if fuse.just_finished() {
+            commands.trigger_targets(DetonateEvent, entity);
+        }
+    }
+}
+
+fn on_detonate(
+    trigger: Trigger<DetonateEvent>,
+    mut commands: Commands,
+    datum: Res<data::ProjectileDataSource>,
+    spatial_query: SpatialQuery,
+    projectile_query: Query<(&Projectile, &GlobalTransform)>,
+    target_query: Query<(&GlobalTransform, Option<&RigidBody>)>,
+) {
+    let entity = trigger.entity();
+
+    // a projectile can detonate from both its fuse and a collision in the same frame
+    let Ok((projectile, global_transform)) = projectile_query.get(entity) else {
+        return;
+    };
+
+    let data = datum.get(&projectile.r#type).unwrap();
+    let origin = global_transform.translation();
+
+    commands.entity(entity).despawn_recursive();
+
+    let Some(explosion) = data.explosion else {
+        return;
+    };
+
+    info!("{} exploded at {}", data.name, origin);
+
+    let los_filter = SpatialQueryFilter::from_mask(EXPLOSION_BLOCKING_LAYERS);
+
+    let targets = spatial_query.shape_intersections(
+        &Collider::sphere(explosion.radius),
+        origin,
+        Quat::IDENTITY,
+        &SpatialQueryFilter::from_mask(EXPLOSION_TARGET_LAYERS).with_excluded_entities([entity]),
+    );
+
+    for target in targets {
+        let Ok((target_global_transform, rigid_body)) = target_query.get(target) else {
+            continue;
+        };
+
+        let to_target = target_global_transform.translation() - origin;
+        let distance = to_target.length();
+
+        // world geometry between the explosion and the target shields it
+        if let Ok(direction) = Dir3::new(to_target) {
+            if spatial_query
+                .cast_ray(origin, direction, distance, true, &los_filter)
+                .is_some()
+            {
+                continue;
+            }
+        }
+
+        let scale = explosion.falloff
+            + (1.0 - explosion.falloff) * (1.0 - (distance / explosion.radius).clamp(0.0, 1.0));
+
+        commands.trigger_targets(
+            health::DamageEvent {
+                source: entity,
+                instigator: projectile.owner,
+                weapon_type: None,
+                hit_zone: None,
+                amount: (explosion.damage as f32 * scale).round() as usize,
+            },
+            target,
+        );
+
+        if rigid_body.is_some_and(|rigid_body| rigid_body.is_dynamic()) {
+            commands.entity(target).insert(ExternalImpulse::new(
+                to_target.normalize_or(Vec3::Y) * explosion.impulse * scale,
+            ));
+        }
+    }
+
+    commands.trigger(ExplosionEvent {
+        owner: projectile.owner,
+        origin,
+        radius: explosion.radius,
+    });
+}
+
+pub fn load_projectile_assets(
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
+    datum: &data::ProjectileDatum,
+    projectile_type: data::ProjectileType,
 ) -> assets::MeshMaterial {
+    let data = datum.get(&projectile_type).unwrap();
+
+    let color = match projectile_type {
+        data::ProjectileType::Bullet => css::BLACK,
+        data::ProjectileType::Rocket => css::ORANGE_RED,
+        data::ProjectileType::Grenade => css::DARK_OLIVEGREEN,
+    };
+
     assets::MeshMaterial {
-        mesh: meshes.add(Sphere::new(BULLET_RADIUS)),
-        material: materials.add(Color::from(css::BLACK)),
+        mesh: meshes.add(Sphere::new(data.radius)),
+        material: materials.add(Color::from(color)),
     }
 }
 
 #[allow(clippy::too_many_arguments)]
-fn spawn_projectile<'a>(
+pub fn spawn_projectile<'a>(
     commands: &'a mut Commands,
-    model: (Mesh3d, MeshMaterial3d<StandardMaterial>),
-    name: impl Into<Cow<'static, str>>,
-    radius: f32,
+    game_assets: &assets::GameAssets,
+    datum: &data::ProjectileDatum,
+    projectile_type: data::ProjectileType,
     owner: Entity,
     origin: Vec3,
     direction: Dir3,
     speed: f32,
 ) -> EntityCommands<'a> {
+    let data = datum.get(&projectile_type).unwrap();
+
     let mut commands = commands.spawn((
         Transform::from_translation(origin).looking_to(direction, Vec3::Y),
         Visibility::default(),
         CollidingEntities::default(),
-        Name::new(name),
-        Projectile::new(owner),
+        Name::new(data.name.clone()),
+        Projectile::new(owner, projectile_type),
     ));
 
     commands.insert((
         RigidBody::Dynamic,
-        Collider::sphere(radius),
+        Collider::sphere(data.radius),
         CollisionLayers::new(GameCollisionLayers::Projectile, PROJECTILE_INTERACT_LAYERS),
-        Mass(MASS),
+        Mass(data.mass),
+        GravityScale(data.gravity_scale),
         LinearVelocity(speed * direction),
-        LockedAxes::ROTATION_LOCKED,
         //SweptCcd::default(),
     ));
 
+    match data.ballistic {
+        Some(ballistic) => {
+            commands.insert((
+                Restitution::new(ballistic.restitution),
+                Friction::new(ballistic.friction),
+                AngularDamping(ballistic.angular_damping),
+            ));
+        }
+        None => {
+            commands.insert(LockedAxes::ROTATION_LOCKED);
+        }
+    }
+
+    if let Some(fuse) = data.fuse {
+        commands.insert(Fuse::new(fuse));
+    }
+
+    let model = game_assets.gen_projectile_mesh_components(projectile_type);
     commands.with_children(|parent| {
         parent.spawn((model, Name::new("Model"), ProjectileModel));
     });
@@ -115,22 +337,159 @@ fn spawn_projectile<'a>(
     commands
 }
 
-pub fn spawn_bullet<'a>(
-    commands: &'a mut Commands,
-    game_assets: &assets::GameAssets,
-    owner: Entity,
+// solves the launch velocity at the given angle that lands on the target
+// returns the velocity and the time of flight
+pub fn solve_ballistic_velocity(
     origin: Vec3,
-    direction: Dir3,
-    speed: f32,
-) -> EntityCommands<'a> {
-    spawn_projectile(
-        commands,
-        game_assets.gen_bullet_mesh_components(),
-        "Bullet",
-        BULLET_RADIUS,
-        owner,
-        origin,
-        direction,
-        speed,
-    )
+    target: Vec3,
+    angle: f32,
+    max_range: f32,
+    gravity: f32,
+) -> Option<(Vec3, f32)> {
+    let offset = target - origin;
+    let horizontal = Vec3::new(offset.x, 0.0, offset.z);
+    let direction = horizontal.normalize_or_zero();
+    let distance = horizontal.length().min(max_range);
+
+    let (sin, cos) = angle.sin_cos();
+    let denominator = 2.0 * cos * cos * (distance * angle.tan() - offset.y);
+    if denominator <= 0.0 {
+        return None;
+    }
+
+    let speed = (gravity * distance * distance / denominator).sqrt();
+    if speed <= f32::EPSILON {
+        return Some((Vec3::ZERO, 0.0));
+    }
+
+    Some((
+        direction * speed * cos + Vec3::Y * speed * sin,
+        distance / (speed * cos),
+    ))
+}
+
+pub fn ballistic_position(origin: Vec3, velocity: Vec3, gravity: Vec3, time: f32) -> Vec3 {
+    origin + velocity * time + 0.5 * gravity * time * time
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::PLAYER_INTERACT_LAYERS;
+
+    #[derive(Debug, Default, Resource)]
+    struct Hits(Vec<(Entity, Entity)>);
+
+    fn create_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            PhysicsPlugins::default(),
+            ProjectilePlugin,
+        ))
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Hits>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 64.0,
+        )))
+        .add_observer(
+            |trigger: Trigger<ProjectileCollisionEvent>, mut hits: ResMut<Hits>| {
+                hits.0.push((trigger.entity(), trigger.target));
+            },
+        );
+
+        data::register_data(&mut app.world_mut().commands());
+        app.world_mut().flush();
+
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    fn spawn_target(app: &mut App, position: Vec3) -> Entity {
+        app.world_mut()
+            .spawn((
+                Transform::from_translation(position),
+                RigidBody::Static,
+                Collider::capsule(0.5, 1.0),
+                CollisionLayers::new(GameCollisionLayers::Player, PLAYER_INTERACT_LAYERS),
+            ))
+            .id()
+    }
+
+    fn spawn_bullet(app: &mut App, owner: Entity, position: Vec3) -> Entity {
+        app.world_mut()
+            .spawn((
+                Transform::from_translation(position),
+                CollidingEntities::default(),
+                Projectile::new(owner, data::ProjectileType::Bullet),
+                RigidBody::Dynamic,
+                Collider::sphere(0.1),
+                CollisionLayers::new(GameCollisionLayers::Projectile, PROJECTILE_INTERACT_LAYERS),
+                GravityScale(0.0),
+            ))
+            .id()
+    }
+
+    fn step(app: &mut App, seconds: f32) {
+        for _ in 0..(seconds * 64.0) as usize {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn owner_is_ignored_during_grace_period() {
+        let mut app = create_app();
+
+        let owner = spawn_target(&mut app, Vec3::ZERO);
+        let projectile = spawn_bullet(&mut app, owner, Vec3::ZERO);
+
+        step(&mut app, OWNER_GRACE_PERIOD * 0.5);
+
+        assert!(
+            !app.world()
+                .resource::<Collisions>()
+                .contains(projectile, owner)
+        );
+        assert!(app.world().resource::<Hits>().0.is_empty());
+        assert!(app.world().get_entity(projectile).is_ok());
+    }
+
+    #[test]
+    fn owner_is_hit_after_grace_period() {
+        let mut app = create_app();
+
+        let owner = spawn_target(&mut app, Vec3::ZERO);
+        let projectile = spawn_bullet(&mut app, owner, Vec3::ZERO);
+
+        step(&mut app, OWNER_GRACE_PERIOD * 0.5);
+        assert!(app.world().resource::<Hits>().0.is_empty());
+
+        step(&mut app, OWNER_GRACE_PERIOD);
+
+        assert_eq!(app.world().resource::<Hits>().0, vec![(projectile, owner)]);
+        assert!(app.world().get_entity(projectile).is_err());
+    }
+
+    #[test]
+    fn non_owner_is_hit_during_grace_period() {
+        let mut app = create_app();
+
+        let owner = spawn_target(&mut app, Vec3::ZERO);
+        let target = spawn_target(&mut app, Vec3::X * 10.0);
+        let projectile = spawn_bullet(&mut app, owner, Vec3::X * 10.0);
+
+        step(&mut app, OWNER_GRACE_PERIOD * 0.5);
+
+        assert_eq!(app.world().resource::<Hits>().0, vec![(projectile, target)]);
+        assert!(app.world().get_entity(projectile).is_err());
+    }
 }
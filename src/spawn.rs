@@ -1,9 +1,350 @@
+use avian3d::prelude::*;
 use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    AppState, GameCollisionLayers, GameMode, RandomSource, RandomStream, assets, container, data,
+    health, inventory, loot, player,
+};
+
+// spawns with anything in this radius are considered blocked
+const SPAWN_CLEARANCE_RADIUS: f32 = 0.5;
+const SPAWN_BLOCKING_LAYERS: [GameCollisionLayers; 2] =
+    [GameCollisionLayers::World, GameCollisionLayers::Player];
 
 #[derive(Debug, Component)]
 #[require(Transform)]
 pub struct PlayerSpawn;
 
-#[derive(Debug, Component)]
+// how long before a loot respawn to warn about it
+const LOOT_RESPAWN_WARNING: f32 = 3.0;
+
+#[derive(Debug, Default, Component)]
 #[require(Transform)]
-pub struct GroundLootSpawn;
+pub struct GroundLootSpawn {
+    // loot is only spawned once if this is None
+    respawn_interval: Option<f32>,
+    loot: Option<Entity>,
+    respawn_timer: Option<Timer>,
+    respawn_warned: bool,
+}
+
+impl GroundLootSpawn {
+    pub fn set_respawn_interval(&mut self, respawn_interval: Option<f32>) {
+        self.respawn_interval = respawn_interval;
+    }
+
+    pub fn set_loot(&mut self, loot: Entity) {
+        self.loot = Some(loot);
+        self.respawn_timer = None;
+    }
+}
+
+// loot is about to respawn at the target spawn
+#[derive(Debug, Event)]
+pub struct LootRespawnWarningEvent {
+    pub remaining: f32,
+}
+
+#[derive(Debug, Resource, Reflect)]
+pub struct RespawnSettings {
+    pub delay: f32,
+    // spawn protection lasts until the player fires or this runs out
+    pub spawn_protection: f32,
+}
+
+impl Default for RespawnSettings {
+    fn default() -> Self {
+        Self {
+            delay: 5.0,
+            spawn_protection: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct PendingRespawn {
+    timer: Timer,
+    local: bool,
+
+    // only warn once while every spawn is blocked
+    blocked: bool,
+}
+
+impl PendingRespawn {
+    fn new(delay: f32, local: bool) -> Self {
+        Self {
+            timer: Timer::from_seconds(delay, TimerMode::Once),
+            local,
+            blocked: false,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+#[require(health::Invulnerable)]
+pub struct SpawnProtection(Timer);
+
+impl SpawnProtection {
+    pub fn new(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+#[derive(Debug)]
+pub struct SpawnPlugin;
+
+impl Plugin for SpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                update_respawns,
+                update_spawn_protection,
+                update_loot_respawns,
+            )
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_observer(on_player_death)
+        .init_resource::<RespawnSettings>()
+        .register_type::<RespawnSettings>();
+    }
+}
+
+fn on_player_death(
+    trigger: Trigger<health::DeathEvent>,
+    mut commands: Commands,
+    settings: Res<RespawnSettings>,
+    death_box_settings: Res<container::DeathBoxSettings>,
+    game_assets: Res<assets::GameAssets>,
+    player_query: Query<
+        (
+            Has<player::LocalPlayer>,
+            &inventory::Inventory,
+            &GlobalTransform,
+            &Name,
+        ),
+        With<player::Player>,
+    >,
+) {
+    let Ok((local, inventory, global_transform, name)) = player_query.get(trigger.entity()) else {
+        return;
+    };
+
+    container::spawn_death_box(
+        &mut commands,
+        &game_assets,
+        &death_box_settings,
+        name.as_str(),
+        inventory,
+        global_transform.translation(),
+    );
+
+    info!(
+        "player {} died, respawning in {}s",
+        trigger.entity(),
+        settings.delay
+    );
+
+    commands.entity(trigger.entity()).despawn_recursive();
+
+    queue_player_spawn(&mut commands, settings.delay, local);
+}
+
+// spawns the player once a spawn is open, after the given delay
+pub fn queue_player_spawn(commands: &mut Commands, delay: f32, local: bool) {
+    commands.spawn((
+        Name::new("Pending Respawn"),
+        PendingRespawn::new(delay, local),
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_respawns(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<RespawnSettings>,
+    game_assets: Res<assets::GameAssets>,
+    game_mode: Res<GameMode>,
+    game_mode_datum: Res<data::GameModeDataSource>,
+    mut random: ResMut<RandomSource>,
+    spatial_query: SpatialQuery,
+    mut respawn_query: Query<(Entity, &mut PendingRespawn)>,
+    player_spawn_query: Query<&GlobalTransform, With<PlayerSpawn>>,
+    enemy_query: Query<(&GlobalTransform, &health::Health), With<player::Player>>,
+) {
+    for (entity, mut respawn) in respawn_query.iter_mut() {
+        respawn.timer.tick(time.delta());
+        if !respawn.timer.finished() {
+            continue;
+        }
+
+        // TODO: teammates aren't enemies
+        let enemies = enemy_query
+            .iter()
+            .filter(|(_, health)| !health.is_dead())
+            .map(|(global_transform, _)| global_transform.translation())
+            .collect::<Vec<_>>();
+
+        let Some(player_spawn) = choose_player_spawn(
+            &spatial_query,
+            &mut random,
+            player_spawn_query.iter(),
+            &enemies,
+        ) else {
+            if !respawn.blocked {
+                warn!("no open player spawns, retrying");
+                respawn.blocked = true;
+            }
+            continue;
+        };
+
+        if !respawn.local {
+            warn!("TODO: respawn remote players");
+        }
+
+        player::spawn_player(
+            &mut commands,
+            &game_assets,
+            player_spawn,
+            game_mode.get_inventory_capacity(&game_mode_datum),
+        )
+        .insert(SpawnProtection::new(settings.spawn_protection));
+
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_loot_respawns(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_assets: Res<assets::GameAssets>,
+    weapon_datum: Res<data::WeaponDataSource>,
+    ammo_datum: Res<data::AmmoDataSource>,
+    consumable_datum: Res<data::ConsumableDataSource>,
+    mut random: ResMut<RandomSource>,
+    mut loot_spawn_query: Query<(Entity, &GlobalTransform, &mut GroundLootSpawn)>,
+    ground_loot_query: Query<(), With<loot::GroundLoot>>,
+) {
+    for (entity, global_transform, mut loot_spawn) in loot_spawn_query.iter_mut() {
+        let Some(respawn_interval) = loot_spawn.respawn_interval else {
+            continue;
+        };
+
+        // partially picked up loot doesn't count as taken
+        if let Some(loot) = loot_spawn.loot {
+            if ground_loot_query.contains(loot) {
+                continue;
+            }
+
+            info!(
+                "loot taken from spawn {}, respawning in {}s",
+                entity, respawn_interval
+            );
+            loot_spawn.loot = None;
+            loot_spawn.respawn_timer = Some(Timer::from_seconds(respawn_interval, TimerMode::Once));
+            loot_spawn.respawn_warned = false;
+        }
+
+        let Some(respawn_timer) = loot_spawn.respawn_timer.as_mut() else {
+            continue;
+        };
+
+        respawn_timer.tick(time.delta());
+        let remaining = respawn_timer.remaining_secs();
+        let finished = respawn_timer.finished();
+
+        // short intervals warn as soon as the loot is taken
+        if !loot_spawn.respawn_warned && !finished && remaining <= LOOT_RESPAWN_WARNING {
+            commands.trigger_targets(LootRespawnWarningEvent { remaining }, entity);
+            loot_spawn.respawn_warned = true;
+        }
+
+        if !finished {
+            continue;
+        }
+
+        let item = inventory::InventoryItem::random_loot(
+            &mut random,
+            &weapon_datum,
+            &ammo_datum,
+            &consumable_datum,
+        );
+        info!("respawn loot {:?} at spawn {}", item, entity);
+
+        let loot = loot::spawn_ground_loot(
+            &mut commands,
+            &game_assets,
+            item,
+            global_transform.compute_transform(),
+        )
+        .insert(loot::SpawnedLoot)
+        .id();
+        loot_spawn.set_loot(loot);
+    }
+}
+
+fn update_spawn_protection(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut protection_query: Query<(Entity, &mut SpawnProtection)>,
+) {
+    for (entity, mut protection) in protection_query.iter_mut() {
+        protection.0.tick(time.delta());
+        if protection.0.finished() {
+            remove_spawn_protection(&mut commands, entity);
+        }
+    }
+}
+
+pub fn remove_spawn_protection(commands: &mut Commands, entity: Entity) {
+    info!("removing spawn protection from {}", entity);
+
+    commands
+        .entity(entity)
+        .remove::<(SpawnProtection, health::Invulnerable)>();
+}
+
+fn is_spawn_blocked(spatial_query: &SpatialQuery, position: Vec3) -> bool {
+    !spatial_query
+        .shape_intersections(
+            &Collider::sphere(SPAWN_CLEARANCE_RADIUS),
+            position,
+            Quat::IDENTITY,
+            &SpatialQueryFilter::from_mask(SPAWN_BLOCKING_LAYERS),
+        )
+        .is_empty()
+}
+
+// picks the open spawn farthest from any enemy
+// or a random open spawn if there aren't any enemies
+pub fn choose_player_spawn<'a>(
+    spatial_query: &SpatialQuery,
+    random: &mut RandomSource,
+    player_spawns: impl IntoIterator<Item = &'a GlobalTransform>,
+    enemies: &[Vec3],
+) -> Option<&'a GlobalTransform> {
+    let player_spawns = player_spawns
+        .into_iter()
+        .filter(|player_spawn| !is_spawn_blocked(spatial_query, player_spawn.translation()))
+        .collect::<Vec<_>>();
+
+    if enemies.is_empty() {
+        return player_spawns
+            .choose(random.stream(RandomStream::Spawns))
+            .copied();
+    }
+
+    let enemy_distance = |player_spawn: &GlobalTransform| {
+        let position = player_spawn.translation();
+        enemies
+            .iter()
+            .map(|enemy| enemy.distance_squared(position))
+            .fold(f32::MAX, f32::min)
+    };
+
+    player_spawns
+        .into_iter()
+        .max_by(|a, b| enemy_distance(a).total_cmp(&enemy_distance(b)))
+}
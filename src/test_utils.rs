@@ -0,0 +1,17 @@
+use bevy::{ecs::system::SystemState, prelude::*};
+
+use crate::{data, inventory};
+
+// a world with all of the game data registered
+pub fn create_world() -> World {
+    let mut world = World::new();
+    data::register_data(&mut world.commands());
+    world.flush();
+    world
+}
+
+// runs f with the item data from the world
+pub fn with_item_data<R>(world: &mut World, f: impl FnOnce(&inventory::ItemData) -> R) -> R {
+    let mut state = SystemState::<inventory::ItemData>::new(world);
+    f(&state.get(world))
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+use rand::prelude::*;
 
-use crate::{assets, data, projectile};
+use crate::{RandomSource, RandomStream, assets, data, health, projectile};
 
 #[derive(Debug, Reflect)]
 pub struct Weapon {
@@ -60,6 +61,9 @@ impl Weapon {
     }
 }
 
+#[derive(Debug, Deref, Component)]
+pub struct WeaponProjectile(data::WeaponType);
+
 #[derive(Debug, Event)]
 struct FireWeaponEvent {
     owner: Entity,
@@ -92,26 +96,61 @@ fn on_fire_weapon(
     trigger: Trigger<FireWeaponEvent>,
     mut commands: Commands,
     game_assets: Res<assets::GameAssets>,
+    mut random: ResMut<RandomSource>,
     datum: Res<data::WeaponDataSource>,
+    projectile_datum: Res<data::ProjectileDataSource>,
 ) {
     let data = datum.get(&trigger.weapon_type).unwrap();
 
-    // TODO: weapon data determines the projectile to spawn here
-    projectile::spawn_bullet(
+    let spread = data.spread.to_radians();
+    let angle = random
+        .stream(RandomStream::Spread)
+        .random_range(-spread..=spread);
+    let direction = Quat::from_rotation_y(angle) * trigger.direction;
+
+    projectile::spawn_projectile(
         &mut commands,
         &game_assets,
+        &projectile_datum,
+        data.projectile_type,
         trigger.owner,
         trigger.origin,
-        trigger.direction,
+        direction,
         data.projectile_speed,
     )
+    .insert(WeaponProjectile(trigger.weapon_type))
     .observe(on_bullet_collision);
 }
 
-fn on_bullet_collision(trigger: Trigger<projectile::ProjectileCollisionEvent>) {
+fn on_bullet_collision(
+    trigger: Trigger<projectile::ProjectileCollisionEvent>,
+    mut commands: Commands,
+    datum: Res<data::WeaponDataSource>,
+    projectile_query: Query<&WeaponProjectile>,
+) {
     info!(
-        "bullet collision for {}: {}",
+        "bullet collision for {}: {} ({:?})",
         trigger.entity(),
-        trigger.target
+        trigger.target,
+        trigger.hit_zone
+    );
+
+    // the projectile may already be gone from an earlier collision this frame
+    let Ok(weapon_projectile) = projectile_query.get(trigger.entity()) else {
+        return;
+    };
+
+    let weapon_type = **weapon_projectile;
+    let data = datum.get(&weapon_type).unwrap();
+
+    commands.trigger_targets(
+        health::DamageEvent {
+            source: trigger.entity(),
+            instigator: trigger.owner,
+            weapon_type: Some(weapon_type),
+            hit_zone: trigger.hit_zone,
+            amount: data.get_damage(trigger.hit_zone),
+        },
+        trigger.target,
     );
 }
@@ -1,7 +1,7 @@
 use avian3d::prelude::*;
 use bevy::{color::palettes::css, prelude::*};
 
-use crate::{GameCollisionLayers, WORLD_INTERACT_LAYERS, assets, spawn};
+use crate::{GameCollisionLayers, WORLD_INTERACT_LAYERS, assets, container, interactables, spawn};
 
 const CEILING_HEIGHT: f32 = 10.0;
 
@@ -111,7 +111,12 @@ fn spawn_box(
         RigidBody::Static,
         Collider::cuboid(BOX_X_LENGTH, BOX_Y_LENGTH, BOX_Z_LENGTH),
         CollisionLayers::new(GameCollisionLayers::World, WORLD_INTERACT_LAYERS),
+        container::Container::default(),
     ));
+
+    commands.with_children(|parent| {
+        interactables::spawn_interactable(parent, interactables::InteractableType::Container);
+    });
 }
 
 fn spawn_crate(
@@ -130,7 +135,12 @@ fn spawn_crate(
         RigidBody::Static,
         Collider::cuboid(CRATE_X_LENGTH, CRATE_Y_LENGTH, CRATE_Z_LENGTH),
         CollisionLayers::new(GameCollisionLayers::World, WORLD_INTERACT_LAYERS),
+        container::Container::default(),
     ));
+
+    commands.with_children(|parent| {
+        interactables::spawn_interactable(parent, interactables::InteractableType::Container);
+    });
 }
 
 pub fn load_floor_assets(
@@ -278,24 +288,24 @@ pub fn spawn_world(commands: &mut Commands, game_assets: &assets::GameAssets) {
     commands.spawn((
         Transform::from_translation(Vec3::new(-3.5, 1.0, -2.0)),
         Name::new("Ground Loot Spawn"),
-        spawn::GroundLootSpawn,
+        spawn::GroundLootSpawn::default(),
     ));
 
     commands.spawn((
         Transform::from_translation(Vec3::new(3.5, 1.0, -2.0)),
         Name::new("Ground Loot Spawn"),
-        spawn::GroundLootSpawn,
+        spawn::GroundLootSpawn::default(),
     ));
 
     commands.spawn((
         Transform::from_translation(Vec3::new(3.5, 1.0, 2.0)),
         Name::new("Ground Loot Spawn"),
-        spawn::GroundLootSpawn,
+        spawn::GroundLootSpawn::default(),
     ));
 
     commands.spawn((
         Transform::from_translation(Vec3::new(-3.5, 1.0, 2.0)),
         Name::new("Ground Loot Spawn"),
-        spawn::GroundLootSpawn,
+        spawn::GroundLootSpawn::default(),
     ));
 }